@@ -10,6 +10,7 @@ pub use markdown::MarkdownFormatter;
 #[macro_use]
 mod operator_documentation;
 pub use operator_documentation::OperatorDocumentation;
+pub(crate) use operator_documentation::all as all_operator_documentation;
 
 mod static_docs;
 pub use static_docs::DocumentationTemplate;
@@ -1,10 +1,20 @@
 use crate::pest::Rule;
+
+/// Documentation for an operator or language construct
 pub struct OperatorDocumentation {
+    /// Grammar rules covered by this entry
     pub rules: &'static [Rule],
+
+    /// Name of the operator
     pub name: &'static str,
+
+    /// The forms the operator can take in a script
     pub symbols: &'static [&'static str],
 
+    /// Description of the operator
     pub description: &'static str,
+
+    /// Example usages of the operator
     pub examples: &'static str,
 }
 
@@ -269,7 +269,15 @@ lazy_static! {
                     - `\\n` Newline
                     - `\\r` Carriage-return
                     - `\\t` Tab
+                    - `\\0` Null character
                     - `\\\\` Literal backslash
+                    - `\\xNN` ASCII character with the hex code NN, up to 7F
+                    - `\\u{NNNN}` Unicode character with the hex code point NNNN, of 1 to 6 digits
+
+                    Any other escape sequence is an error.
+                    Raw strings, such as `r'C:\\path'`, ignore escape sequences entirely.
+
+                    Strings enclosed in backticks can also embed expressions in curly braces - see [string interpolation](<#string-interpolation>).
 
                     **Casting:**
                     It can be cast from any type, and cast to array or object.
@@ -54,6 +54,16 @@ pub enum ErrorDetails {
         msg: String,
     },
 
+    /// An error raised by a script, with a user-defined code
+    #[error("{code}: {msg}")]
+    UserDefined {
+        /// Code identifying the kind of error
+        code: String,
+
+        /// Message describing the error
+        msg: String,
+    },
+
     /// An error used to return a value from a function early
     #[error("Returned from the root scope")]
     Return {
@@ -61,11 +71,21 @@ pub enum ErrorDetails {
         value: Value,
     },
 
+    /// An error used to hand a self-recursive tail call back to the calling function
+    #[error("Tail call from outside a function")]
+    TailCall {
+        /// Arguments for the next iteration of the function
+        arguments: Vec<Value>,
+    },
+
     /// An error used to skip a value from a loop
     #[error("Skipped from outside a loop")]
-    Skip,
+    Skip {
+        /// Value added to the loop's results, if any
+        value: Option<Value>,
+    },
 
-    /// An error used to skip a value from a loop
+    /// An error used to break out of a loop
     #[error("Break called from outside a loop")]
     Break {
         /// Value being returned
@@ -171,6 +191,13 @@ pub enum ErrorDetails {
         end: String,
     },
 
+    /// An error caused by an unrecognized backslash escape in a string literal
+    #[error("Invalid escape sequence `{sequence}` in string")]
+    InvalidEscape {
+        /// The escape sequence, including the backslash
+        sequence: String,
+    },
+
     /// An error caused by a value being out of range
     #[error("Arithmetic overflow")]
     Overflow,
@@ -210,6 +237,20 @@ pub enum ErrorDetails {
     #[error("Array empty")]
     ArrayEmpty,
 
+    /// Caused by a missing key in an object destructuring assignment
+    #[error("Object has no key '{key}'. Use {{{key}?}} to assign nil instead")]
+    DestructuringKey {
+        /// Key that was not found
+        key: String,
+    },
+
+    /// An error caused by using the spread operator on a non-array value
+    #[error("Only arrays can be spread, found {actual_type}")]
+    SpreadType {
+        /// Type of the value being spread
+        actual_type: ValueType,
+    },
+
     ///////////////////////////////////////////////////////////////////////////
     // Function Errors
     // Deals with issues during builtin, user, or extension function calls
@@ -239,6 +280,52 @@ pub enum ErrorDetails {
     /// An error caused by a function calling itself too many times
     #[error("Recursive function went too deep")]
     StackOverflow,
+
+    /// An error caused by the parser exceeding `ParserOptions::pest_call_limit`
+    #[error("Input is too large or too deeply nested to parse within the pest call limit; raise ParserOptions::pest_call_limit, or set it to 0 for trusted input")]
+    PestCallLimit,
+
+    /// An error caused by exceeding the maximum depth of nested function calls
+    #[error("Maximum function call depth of {limit} exceeded")]
+    CallDepth {
+        /// The configured call depth limit
+        limit: usize
+    },
+
+    /// An error caused by calling a function that is disabled in sandbox mode
+    #[error("{name}() is disabled in sandbox mode")]
+    Sandboxed {
+        /// Name of the function being called
+        name: String,
+    },
+
+    /// An error caused by exceeding the maximum nesting depth of eval and include
+    #[error("Maximum eval/include nesting depth of {limit} exceeded")]
+    EvalDepth {
+        /// The configured nesting limit
+        limit: usize
+    },
+
+    /// An error caused by a script producing more values than allowed, in its output or in a single array
+    #[error("Script produced more than {limit} values")]
+    OutputSize {
+        /// The configured output limit
+        limit: usize
+    },
+
+    /// An error caused by importing a script under a name that is already in use
+    #[error("{name} is already defined; import the script under a different namespace")]
+    ImportCollision {
+        /// Name that was already defined
+        name: String,
+    },
+
+    /// An error caused by a file including itself, directly or through other files
+    #[error("{path} includes itself; recursive includes are not allowed")]
+    IncludeCycle {
+        /// Path of the file that was included again
+        path: String,
+    },
     
     /// An error caused by calling a function with the wrong type of argument
     #[error("Expected {expected_type} value for argument {arg} of `{signature}`")]
@@ -254,6 +341,16 @@ pub enum ErrorDetails {
 
     },
 
+    /// An error caused by a function returning a value that does not match its return type
+    #[error("Expected `{signature}` to return a {expected_type} value")]
+    FunctionReturnType {
+        /// Type that was declared
+        expected_type: ValueType,
+
+        /// Signature of the function called
+        signature: String,
+    },
+
     /// An error caused by calling a function that does not exist
     #[error("Undefined function {name}. You can define a function with {name}(a, b, c) = ...")]
     FunctionName {
@@ -321,3 +418,71 @@ pub enum ErrorDetails {
     #[error("{0}")]
     SerdeJsonError(#[from] serde_json::Error),
 }
+
+impl ErrorDetails {
+    /// Returns the name of this kind of error, such as `VariableName` or `Overflow`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Internal { .. } => "Internal",
+            Self::EmptyBlock => "EmptyBlock",
+            Self::Syntax { .. } => "Syntax",
+            Self::Fatal { .. } => "Fatal",
+            Self::Timeout => "Timeout",
+            Self::Custom { .. } => "Custom",
+            Self::UserDefined { .. } => "UserDefined",
+            Self::Return { .. } => "Return",
+            Self::TailCall { .. } => "TailCall",
+            Self::Skip { .. } => "Skip",
+            Self::Break { .. } => "Break",
+            Self::ReadOnlyFunction { .. } => "ReadOnlyFunction",
+            Self::NoElseBlock => "NoElseBlock",
+            Self::UnterminatedReturn => "UnterminatedReturn",
+            Self::UnexpectedDecorator => "UnexpectedDecorator",
+            Self::UnterminatedComment => "UnterminatedComment",
+            Self::UnterminatedArray => "UnterminatedArray",
+            Self::UnterminatedObject => "UnterminatedObject",
+            Self::UnterminatedParen => "UnterminatedParen",
+            Self::UnterminatedLinebreak => "UnterminatedLinebreak",
+            Self::UnterminatedLiteral => "UnterminatedLiteral",
+            Self::NonExhaustiveSwitch => "NonExhaustiveSwitch",
+            Self::UnreachableSwitchCase => "UnreachableSwitchCase",
+            Self::SwitchCaseTypeMismatch { .. } => "SwitchCaseTypeMismatch",
+            Self::ConstantValue => "ConstantValue",
+            Self::RangeTypeMismatch => "RangeTypeMismatch",
+            Self::InvalidRange { .. } => "InvalidRange",
+            Self::RangeStartGT { .. } => "RangeStartGT",
+            Self::InvalidEscape { .. } => "InvalidEscape",
+            Self::Overflow => "Overflow",
+            Self::DestructuringAssignment { .. } => "DestructuringAssignment",
+            Self::ValueFormat { .. } => "ValueFormat",
+            Self::Range { .. } => "Range",
+            Self::VariableName { .. } => "VariableName",
+            Self::ArrayEmpty => "ArrayEmpty",
+            Self::DestructuringKey { .. } => "DestructuringKey",
+            Self::SpreadType { .. } => "SpreadType",
+            Self::DecoratorSignatureArgs { .. } => "DecoratorSignatureArgs",
+            Self::DecoratorSignatureReturn { .. } => "DecoratorSignatureReturn",
+            Self::FunctionCall { .. } => "FunctionCall",
+            Self::StackOverflow => "StackOverflow",
+            Self::PestCallLimit => "PestCallLimit",
+            Self::CallDepth { .. } => "CallDepth",
+            Self::Sandboxed { .. } => "Sandboxed",
+            Self::EvalDepth { .. } => "EvalDepth",
+            Self::OutputSize { .. } => "OutputSize",
+            Self::ImportCollision { .. } => "ImportCollision",
+            Self::IncludeCycle { .. } => "IncludeCycle",
+            Self::FunctionArgumentType { .. } => "FunctionArgumentType",
+            Self::FunctionReturnType { .. } => "FunctionReturnType",
+            Self::FunctionName { .. } => "FunctionName",
+            Self::FunctionArguments { .. } => "FunctionArguments",
+            Self::DecoratorName { .. } => "DecoratorName",
+            Self::UnknownApi { .. } => "UnknownApi",
+            Self::Value(_) => "Value",
+            Self::Io(_) => "Io",
+            Self::Network(_) => "Network",
+            Self::ParseIntError(_) => "ParseIntError",
+            Self::FromUtf8Error(_) => "FromUtf8Error",
+            Self::SerdeJsonError(_) => "SerdeJsonError",
+        }
+    }
+}
@@ -1,6 +1,7 @@
 //! Error handling module.
 //! Defines the Error/ErrorDetails types, and associated traits and macros.
 use crate::Token;
+use polyvalue::{types::Object, Value};
 
 #[macro_use]
 mod macros;
@@ -11,6 +12,9 @@ pub use error_details::ErrorDetails;
 mod traits;
 pub use traits::*;
 
+mod warning;
+pub use warning::Warning;
+
 /// Error type for the Lavendeux parser
 /// Can have optional context [Token], and parent error
 #[derive(Debug)]
@@ -50,6 +54,24 @@ impl<'i> Error {
         }
     }
 
+    /// Returns the innermost error in the chain - the original cause of this error
+    pub fn root_cause(&self) -> &Error {
+        let mut error = self;
+        while let Some(source) = &error.source {
+            error = source;
+        }
+        error
+    }
+
+    /// Returns the name of the kind of error that occurred, such as `VariableName` or `Overflow`
+    /// For errors raised by scripts with a code, this is the code
+    pub fn kind(&self) -> String {
+        match &self.details {
+            ErrorDetails::UserDefined { code, .. } => code.clone(),
+            details => details.kind().to_string(),
+        }
+    }
+
     /// Offset the line-numbers in this and all parent errors
     /// Useful for when a script is included in another script
     /// Or for function calls
@@ -85,6 +107,43 @@ where
     }
 }
 
+/// Represents an error as an object, so that it can be inspected by scripts
+/// Has the keys `kind`, `message`, `line` and `token`, describing the original cause of the error
+/// `line` and `token` are nil if the location of the error is unknown
+impl From<&Error> for Value {
+    fn from(error: &Error) -> Self {
+        let root = error.root_cause();
+
+        // Use the innermost location available
+        let mut context = error.context.as_ref();
+        let mut next = error.source.as_deref();
+        while let Some(source) = next {
+            context = source.context.as_ref().or(context);
+            next = source.source.as_deref();
+        }
+
+        let mut obj = Object::new(Default::default());
+        obj.insert("kind".into(), Value::from(root.kind())).ok();
+        let message = match &root.details {
+            ErrorDetails::UserDefined { msg, .. } => msg.clone(),
+            details => details.to_string(),
+        };
+        obj.insert("message".into(), Value::from(message)).ok();
+        obj.insert(
+            "line".into(),
+            context.map_or(Value::from(false), |c| Value::from(c.line as i64)),
+        )
+        .ok();
+        obj.insert(
+            "token".into(),
+            context.map_or(Value::from(false), |c| Value::from(c.input.to_string())),
+        )
+        .ok();
+
+        obj.into()
+    }
+}
+
 impl std::error::Error for Error {}
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
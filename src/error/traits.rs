@@ -72,7 +72,7 @@ impl<T> WrapSyntaxError<T, Rule> for Result<T, pest::error::Error<Rule>> {
                     }
 
                     ErrorVariant::CustomError { message } if message.contains("call limit") => {
-                        oops!(StackOverflow, token)
+                        oops!(PestCallLimit, token)
                     }
 
                     _ => oops!(
@@ -220,6 +220,7 @@ impl From<Rule> for RuleCategory {
             | Rule::SWITCH_EXPRESSION
             | Rule::FOR_LOOP_EXPRESSION
             | Rule::IF_EXPRESSION
+            | Rule::TRY_EXPRESSION
             | Rule::EXPR => Self::Expression,
 
             Rule::symbol_questionmark => Self::Symbol("?"),
@@ -237,14 +238,18 @@ impl From<Rule> for RuleCategory {
             Rule::EOL => Self::Symbol("EOL"),
 
             Rule::break_keyword => Self::Symbol("break"),
+            Rule::skip_keyword => Self::Symbol("skip"),
             Rule::return_keyword => Self::Symbol("return"),
             Rule::for_keyword => Self::Symbol("for"),
             Rule::in_keyword => Self::Symbol("in"),
             Rule::do_keyword => Self::Symbol("do"),
             Rule::if_keyword => Self::Symbol("if"),
             Rule::else_keyword => Self::Symbol("else"),
+            Rule::elif_keyword => Self::Symbol("elif"),
             Rule::then_keyword => Self::Symbol("then"),
             Rule::match_keyword => Self::Symbol("match"),
+            Rule::try_keyword => Self::Symbol("try"),
+            Rule::catch_keyword => Self::Symbol("catch"),
 
             Rule::POSTFIX_EMPTYINDEX
             | Rule::del_keyword
@@ -313,12 +318,14 @@ impl From<Rule> for RuleCategory {
             | Rule::KEYWORD_EXPRESSION
             | Rule::postfix_operation
             | Rule::postfixcall_args
+            | Rule::postfixcall_arg
             | Rule::POSTFIX_NORMALMODE
             | Rule::POSTFIX_OBJECTMODE
             | Rule::infix_op => Self::Operator,
 
             Rule::ARRAY_TERM => Self::Array,
-            Rule::OBJECT_TERM => Self::Object,
+            Rule::SPREAD_EXPRESSION => Self::Expression,
+            Rule::OBJECT_TERM | Rule::OBJECT_PATTERN => Self::Object,
 
             Rule::TERM
             | Rule::ATOMIC_VALUE
@@ -334,6 +341,9 @@ impl From<Rule> for RuleCategory {
             | Rule::int_literal
             | Rule::bool_literal
             | Rule::string_literal
+            | Rule::byte_string_literal
+            | Rule::raw_string_literal
+            | Rule::interpolated_string_literal
             | Rule::regex_literal => Self::Literal,
 
             Rule::identifier => Self::Identifier,
@@ -352,11 +362,23 @@ impl From<Rule> for RuleCategory {
             Rule::currency_suffix | Rule::currency_symbol => Self::CurrencySymbol,
 
             Rule::object_keyvalue_pair
+            | Rule::object_computed_key
+            | Rule::object_shorthand
+            | Rule::object_pattern_entry
+            | Rule::object_pattern_optional
             | Rule::for_assignment
             | Rule::block_line
             | Rule::for_conditional
+            | Rule::for_else
+            | Rule::catch_binding
             | Rule::switch_case
+            | Rule::SWITCH_RANGE
+            | Rule::SWITCH_BINDING
+            | Rule::SWITCH_GUARD
             | Rule::if_block
+            | Rule::elif_block
+            | Rule::interpolated_text
+            | Rule::interpolated_expression
             | Rule::function_typespec
             | Rule::function_argument
             | Rule::function_name
@@ -0,0 +1,31 @@
+use crate::Token;
+
+/// A non-fatal diagnostic raised by a script
+/// Unlike an [super::Error], a warning does not stop execution
+#[derive(Debug, Clone)]
+pub struct Warning {
+    /// message: Text describing the problem
+    pub message: String,
+
+    /// context: The [Token] of the function call that raised the warning, if known
+    pub context: Option<Token<'static>>,
+}
+
+impl Warning {
+    /// Create a new warning, without a location
+    pub fn new(message: impl Into<String>) -> Self {
+        Warning {
+            message: message.into(),
+            context: None,
+        }
+    }
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.context {
+            Some(context) => write!(f, "{context}\n= Warning: {}", self.message),
+            None => write!(f, "Warning: {}", self.message),
+        }
+    }
+}
@@ -0,0 +1,332 @@
+//! Canonical source formatter
+//! Re-emits a script from its parse tree, normalizing spacing and indentation
+//! Comments and line structure are preserved
+use crate::{pest::LavendeuxParser, syntax_tree::Node, Error, Rule, State};
+use pest::iterators::Pair;
+use std::ops::Range;
+
+const INDENT: &str = "    ";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bracket {
+    None,
+    Open,
+    Close,
+}
+
+#[derive(Debug, Clone)]
+enum Piece {
+    Newline,
+    Text {
+        text: String,
+        glue_left: bool,
+        glue_right: bool,
+        bracket: Bracket,
+    },
+}
+
+/// A comment found in the source of a script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    /// Byte range of the comment in the source
+    pub span: Range<usize>,
+
+    /// Line number on which the comment starts
+    pub line: usize,
+
+    /// Text of the comment, including the delimiters
+    pub text: String,
+}
+
+/// Formats scripts into a canonical layout
+/// Output is stable; formatting already formatted source returns it unchanged
+pub struct Formatter<'i> {
+    input: &'i str,
+    pieces: Vec<Piece>,
+    comments: Vec<Comment>,
+}
+
+impl<'i> Formatter<'i> {
+    /// Parse the given script and re-emit it in canonical form
+    pub fn format(input: &'i str) -> Result<String, Error> {
+        Ok(Self::walk(input)?.render())
+    }
+
+    /// Parse the given script and return the comments it contains, in order
+    pub fn comments(input: &'i str) -> Result<Vec<Comment>, Error> {
+        Ok(Self::walk(input)?.comments)
+    }
+
+    fn walk(input: &'i str) -> Result<Self, Error> {
+        let root = LavendeuxParser::parse2(input, Rule::SCRIPT)?;
+
+        // Build the AST against a throwaway state so that invalid scripts are rejected
+        LavendeuxParser::compile_ast(root.clone(), &mut State::new())?;
+
+        let mut formatter = Self {
+            input,
+            pieces: vec![],
+            comments: vec![],
+        };
+        formatter.visit(root, Rule::SCRIPT);
+        Ok(formatter)
+    }
+
+    /// Rules that are emitted verbatim, even if they contain inner tokens
+    fn is_opaque(rule: Rule) -> bool {
+        matches!(
+            rule,
+            Rule::int_literal
+                | Rule::currency_literal
+                | Rule::interpolated_string_literal
+                | Rule::function_name
+        )
+    }
+
+    fn visit(&mut self, pair: Pair<'i, Rule>, parent: Rule) {
+        stacker::maybe_grow(Node::MIN_STACK, Node::STACK_EXP, || {
+            let rule = pair.as_rule();
+            let span = pair.as_span();
+            if matches!(rule, Rule::INLINE_COMMENT | Rule::BLOCK_COMMENT) {
+                self.push_comment(span.start(), pair.as_str());
+                return;
+            } else if Self::is_opaque(rule) || pair.clone().into_inner().peek().is_none() {
+                self.push_token(rule, parent, pair.as_str());
+                return;
+            }
+
+            // Anything between child tokens is whitespace, comments or anonymous symbols
+            let mut cursor = span.start();
+            for child in pair.into_inner() {
+                self.push_gap(cursor, child.as_span().start(), rule);
+                cursor = child.as_span().end();
+                self.visit(child, rule);
+            }
+            self.push_gap(cursor, span.end(), rule);
+        })
+    }
+
+    fn push_text(&mut self, text: &str, glue_left: bool, glue_right: bool, bracket: Bracket) {
+        if !text.is_empty() {
+            self.pieces.push(Piece::Text {
+                text: text.to_string(),
+                glue_left,
+                glue_right,
+                bracket,
+            });
+        }
+    }
+
+    fn push_token(&mut self, rule: Rule, parent: Rule, text: &str) {
+        let is_object = matches!(parent, Rule::OBJECT_TERM | Rule::OBJECT_PATTERN);
+        match rule {
+            Rule::symbol_comma | Rule::object_pattern_optional => {
+                self.push_text(text, true, false, Bracket::None)
+            }
+            Rule::symbol_colon if parent != Rule::OP_TERNARY => {
+                self.push_text(text, true, false, Bracket::None)
+            }
+
+            Rule::POSTFIX_INC | Rule::POSTFIX_DEC => self.push_text(text, true, false, Bracket::None),
+            Rule::PREFIX_NEG
+            | Rule::PREFIX_INC
+            | Rule::PREFIX_DEC
+            | Rule::PREFIX_BOOL_NOT
+            | Rule::PREFIX_BIT_NOT => self.push_text(text, false, true, Bracket::None),
+            Rule::PREFIX_DEL => {
+                let keyword = text.trim_end_matches(|c: char| c == '@' || c.is_whitespace());
+                if text.ends_with('@') {
+                    self.push_text(&format!("{keyword} @"), false, true, Bracket::None)
+                } else {
+                    self.push_text(keyword, false, false, Bracket::None)
+                }
+            }
+            Rule::OP_RANGE => self.push_text(text, true, true, Bracket::None),
+
+            Rule::array_symbol_opensquare => self.push_text(text, false, true, Bracket::Open),
+            Rule::symbol_opensquare if parent == Rule::object_computed_key => {
+                self.push_text(text, false, true, Bracket::Open)
+            }
+            Rule::symbol_opensquare => self.push_text(text, true, true, Bracket::Open),
+            Rule::symbol_closesquare => self.push_text(text, true, false, Bracket::Close),
+            Rule::symbol_opencurly => self.push_text(text, false, is_object, Bracket::Open),
+            Rule::symbol_closecurly => self.push_text(text, is_object, false, Bracket::Close),
+
+            _ => self.push_text(text, false, false, Bracket::None),
+        }
+    }
+
+    fn push_comment(&mut self, start: usize, text: &str) {
+        let text = text.trim_end();
+        self.comments.push(Comment {
+            span: start..start + text.len(),
+            line: self.input[..start].matches('\n').count() + 1,
+            text: text.to_string(),
+        });
+        self.push_text(text, false, false, Bracket::None);
+    }
+
+    fn push_gap(&mut self, start: usize, end: usize, context: Rule) {
+        let mut rest = &self.input[start..end];
+        while let Some(c) = rest.chars().next() {
+            let offset = end - rest.len();
+            let len = if rest.starts_with("//") {
+                let len = rest.find('\n').unwrap_or(rest.len());
+                self.push_comment(offset, &rest[..len]);
+                len
+            } else if rest.starts_with("/*") {
+                let len = rest.find("*/").map_or(rest.len(), |i| i + 2);
+                self.push_comment(offset, &rest[..len]);
+                len
+            } else if rest.starts_with("->") {
+                self.push_text("->", false, false, Bracket::None);
+                2
+            } else if rest.starts_with("...") {
+                self.push_text("...", false, true, Bracket::None);
+                3
+            } else if rest.starts_with('\\') {
+                // Line continuations are folded into the current line
+                1 + ["\r\n", "\n", "\r", ";"]
+                    .iter()
+                    .find(|eol| rest[1..].starts_with(*eol))
+                    .map_or(0, |eol| eol.len())
+            } else {
+                let is_call = matches!(
+                    context,
+                    Rule::POSTFIX_NORMALMODE | Rule::FUNCTION_ASSIGNMENT_STATEMENT
+                );
+                match c {
+                    '\n' => self.pieces.push(Piece::Newline),
+                    ';' => self.push_text(";", true, false, Bracket::None),
+                    '(' | '[' => self.push_text(&rest[..1], is_call, true, Bracket::Open),
+                    ')' | ']' => self.push_text(&rest[..1], true, false, Bracket::Close),
+                    '{' => self.push_text("{", false, false, Bracket::Open),
+                    '}' => self.push_text("}", false, false, Bracket::Close),
+                    '.' => self.push_text(".", true, true, Bracket::None),
+                    '@' => self.push_text("@", false, true, Bracket::None),
+                    c if c.is_whitespace() => {}
+                    _ => self.push_text(&rest[..c.len_utf8()], false, false, Bracket::None),
+                }
+                c.len_utf8()
+            };
+            rest = &rest[len..];
+        }
+    }
+
+    fn render(self) -> String {
+        let mut output = String::new();
+
+        // Indentation level of each line that left a bracket open
+        let mut open_lines: Vec<usize> = vec![];
+        let mut indent = 0;
+        let mut newlines = 0;
+        let mut glued = true;
+
+        for piece in self.pieces {
+            match piece {
+                Piece::Newline => {
+                    if !output.is_empty() {
+                        newlines += 1;
+                    }
+                }
+                Piece::Text {
+                    text,
+                    glue_left,
+                    glue_right,
+                    bracket,
+                } => {
+                    if newlines > 0 {
+                        // Keep at most one blank line between statements
+                        output.push_str(&"\n".repeat(newlines.min(2)));
+                        newlines = 0;
+
+                        indent = if bracket == Bracket::Close {
+                            open_lines.pop().unwrap_or_default()
+                        } else {
+                            open_lines.last().map_or(0, |i| i + 1)
+                        };
+                        output.push_str(&INDENT.repeat(indent));
+                    } else {
+                        if bracket == Bracket::Close {
+                            open_lines.pop();
+                        }
+                        if !glued && !glue_left {
+                            output.push(' ');
+                        }
+                    }
+
+                    output.push_str(&text);
+                    if bracket == Bracket::Open {
+                        open_lines.push(indent);
+                    }
+                    glued = glue_right;
+                }
+            }
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_formats(input: &str, expected: &str) {
+        let formatted = Formatter::format(input).expect("Failed to format input");
+        assert_eq!(formatted, expected);
+        assert_eq!(
+            Formatter::format(&formatted).expect("Failed to format output"),
+            formatted,
+            "Formatting is not idempotent"
+        );
+    }
+
+    #[test]
+    fn test_format_spacing() {
+        assert_formats("a=1+2*3", "a = 1 + 2 * 3");
+        assert_formats("f(x)=x**2", "f(x) = x ** 2");
+        assert_formats("max( [1,2 , 3] )@hex", "max([1, 2, 3]) @hex");
+        assert_formats("x = {'a':[1,2]}", "x = {'a': [1, 2]}");
+        assert_formats("{a,[k]:1}", "{a, [k]: 1}");
+        assert_formats("-x; 1..5", "-x; 1..5");
+    }
+
+    #[test]
+    fn test_format_blocks() {
+        assert_formats(
+            "if a>1 {\nb=2\n\n\n\nb\n} else {3}",
+            "if a > 1 {\n    b = 2\n\n    b\n} else { 3 }",
+        );
+    }
+
+    #[test]
+    fn test_format_comments() {
+        assert_formats("// hello\nx=1 // one", "// hello\nx = 1 // one");
+        assert_formats("x = /* two */ 2", "x = /* two */ 2");
+    }
+
+    #[test]
+    fn test_comments() {
+        let input = "// first\nx = 1 /* second */ + 2\ny = 3 // third";
+        let comments = Formatter::comments(input).expect("Failed to parse comments");
+        assert_eq!(comments.len(), 3);
+        assert_eq!(comments[0].text, "// first");
+        assert_eq!(comments[0].line, 1);
+        assert_eq!(comments[1].text, "/* second */");
+        assert_eq!(&input[comments[1].span.clone()], "/* second */");
+        assert_eq!(comments[2].line, 3);
+    }
+
+    #[test]
+    fn test_format_invalid() {
+        Formatter::format("x = [1, 2").expect_err("Expected an error");
+    }
+
+    #[test]
+    fn test_format_self_test() {
+        let input = include_str!("../example_scripts/self_test.lav");
+        let formatted = Formatter::format(input).expect("Failed to format self test");
+        assert_eq!(Formatter::format(&formatted).unwrap(), formatted);
+    }
+}
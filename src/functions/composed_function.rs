@@ -0,0 +1,97 @@
+use std::borrow::Cow;
+
+use crate::{syntax_tree::AssignmentTarget, Error, State};
+use polyvalue::{Value, ValueType};
+
+use super::{
+    documentation::UserFunctionDocumentation,
+    std_function::{FunctionArgument, FunctionArgumentType, ParserFunction},
+    FunctionDocumentation,
+};
+
+/// A chain of functions
+/// Calls each function by name in turn, passing the result of one to the next
+#[derive(Debug, Clone)]
+pub struct ComposedFunction {
+    name: String,
+    functions: Vec<String>,
+
+    own_docs: UserFunctionDocumentation,
+}
+impl ParserFunction for ComposedFunction {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn documentation(&self) -> &dyn FunctionDocumentation {
+        &self.own_docs
+    }
+
+    fn documentation_mut(&mut self) -> &mut dyn FunctionDocumentation {
+        &mut self.own_docs
+    }
+
+    fn return_type(&self) -> ValueType {
+        ValueType::Any
+    }
+
+    fn expected_arguments(&self) -> Vec<(Cow<'static, str>, FunctionArgument)> {
+        vec![(
+            Cow::Borrowed("x"),
+            FunctionArgument {
+                expected_type: ValueType::Any,
+                meta: FunctionArgumentType::Standard,
+            },
+        )]
+    }
+
+    fn clone_self(&self) -> Box<dyn ParserFunction> {
+        Box::new(self.clone())
+    }
+
+    fn call(
+        &self,
+        state: &mut State,
+        _reference: Option<&AssignmentTarget>,
+    ) -> Result<Value, Error> {
+        let mut value = match state.get_variable("x").cloned() {
+            Some(value) => value,
+            None => {
+                return oops!(Internal {
+                    msg: "Missing required argument: x".to_string()
+                })
+            }
+        };
+        for function in &self.functions {
+            value = state.call_function(function, vec![value], None)?;
+        }
+        Ok(value)
+    }
+}
+
+impl ComposedFunction {
+    /// Create a function applying the named functions right-to-left; compose(f, g)(x) is f(g(x))
+    pub fn compose(functions: Vec<String>) -> Self {
+        let name = format!("compose({})", functions.join(", "));
+        Self::new(name, functions.into_iter().rev().collect())
+    }
+
+    /// Create a function applying the named functions left-to-right; pipe_fns(f, g)(x) is g(f(x))
+    pub fn pipe(functions: Vec<String>) -> Self {
+        let name = format!("pipe_fns({})", functions.join(", "));
+        Self::new(name, functions)
+    }
+
+    fn new(name: String, functions: Vec<String>) -> Self {
+        ComposedFunction {
+            own_docs: UserFunctionDocumentation {
+                category: "User-Defined Functions".to_string(),
+                description: Some(format!("Composition of {}", functions.join(", "))),
+                ext_description: None,
+                examples: None,
+            },
+            name,
+            functions,
+        }
+    }
+}
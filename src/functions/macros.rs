@@ -151,7 +151,7 @@ macro_rules! define_stddecorator {
             ext_description: $ext_description:literal,
             examples: $examples:literal$(,)?
         },
-        handler = ($hndval:ident) $handler:block$(,)?
+        handler = ($hndval:ident $(, $statevar:ident)?) $handler:block$(,)?
     ) => {
         paste::paste! {
             #[allow(non_camel_case_types)]
@@ -213,6 +213,7 @@ macro_rules! define_stddecorator {
 
                 fn call(&self, state: &mut $crate::State, _: Option<&$crate::AssignmentTarget>) -> Result<$crate::polyvalue::Value, $crate::Error> {
                     let $hndval = $crate::required_arg!(state::$aname);
+                    $(let $statevar: &$crate::State = state;)?
                     let value: Result<String, $crate::Error> = $handler;
                     Ok(value?.into())
                 }
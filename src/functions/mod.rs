@@ -1,3 +1,6 @@
+mod composed_function;
+mod namespaced_function;
+mod partial_function;
 mod std_function;
 mod user_function;
 
@@ -8,6 +11,9 @@ mod documentation;
 pub use documentation::*;
 
 pub use std_function::{FunctionArgument, FunctionArgumentType, ParserFunction};
+pub use composed_function::ComposedFunction;
+pub use namespaced_function::NamespacedFunction;
+pub use partial_function::PartialFunction;
 pub use user_function::UserDefinedFunction;
 
 /// The standard library of functions
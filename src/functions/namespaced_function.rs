@@ -0,0 +1,82 @@
+use std::borrow::Cow;
+
+use crate::{syntax_tree::AssignmentTarget, Error, State};
+use polyvalue::{types::Object, Value, ValueType};
+
+use super::{
+    std_function::{FunctionArgument, ParserFunction},
+    FunctionDocumentation,
+};
+
+/// A function imported from another script under a namespace
+/// Called as `namespace.name(...)`, and can call other functions in its namespace without the prefix
+/// The variables stored in the namespace object are available to it by name
+#[derive(Debug)]
+pub struct NamespacedFunction {
+    name: String,
+    namespace: String,
+    function: Box<dyn ParserFunction>,
+}
+impl ParserFunction for NamespacedFunction {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn documentation(&self) -> &dyn FunctionDocumentation {
+        self.function.documentation()
+    }
+
+    fn documentation_mut(&mut self) -> &mut dyn FunctionDocumentation {
+        self.function.documentation_mut()
+    }
+
+    fn return_type(&self) -> ValueType {
+        self.function.return_type()
+    }
+
+    fn expected_arguments(&self) -> Vec<(Cow<'static, str>, FunctionArgument)> {
+        self.function.expected_arguments()
+    }
+
+    fn clone_self(&self) -> Box<dyn ParserFunction> {
+        Box::new(NamespacedFunction {
+            name: self.name.clone(),
+            namespace: self.namespace.clone(),
+            function: self.function.clone_self(),
+        })
+    }
+
+    fn call(
+        &self,
+        state: &mut State,
+        reference: Option<&AssignmentTarget>,
+    ) -> Result<Value, Error> {
+        // The module's top-level variables are visible to its functions, unless shadowed by an argument
+        let variables = state
+            .global_get_variable(&self.namespace)
+            .and_then(|v| v.clone().as_a::<Object>().ok())
+            .unwrap_or_default();
+        for (name, value) in variables.keys().into_iter().zip(variables.values()) {
+            let name = name.to_string();
+            if state.get_variable(&name).is_none() {
+                state.set_variable_in_scope(&name, value.clone());
+            }
+        }
+
+        state.push_namespace(&self.namespace);
+        let result = self.function.call(state, reference);
+        state.pop_namespace();
+        result
+    }
+}
+
+impl NamespacedFunction {
+    /// Place a function under the given namespace
+    pub fn new(namespace: &str, function: Box<dyn ParserFunction>) -> Self {
+        NamespacedFunction {
+            name: format!("{}.{}", namespace, function.name()),
+            namespace: namespace.to_string(),
+            function,
+        }
+    }
+}
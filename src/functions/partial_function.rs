@@ -0,0 +1,87 @@
+use std::borrow::Cow;
+
+use crate::{syntax_tree::AssignmentTarget, Error, State};
+use polyvalue::{Value, ValueType};
+
+use super::{
+    documentation::UserFunctionDocumentation,
+    std_function::{FunctionArgument, FunctionArgumentType, ParserFunction},
+    FunctionDocumentation,
+};
+
+/// A partially-applied function
+/// Calls another function by name, with a set of fixed arguments placed before any given at call-time
+#[derive(Debug, Clone)]
+pub struct PartialFunction {
+    name: String,
+    function: String,
+    fixed_arguments: Vec<Value>,
+
+    own_docs: UserFunctionDocumentation,
+}
+impl ParserFunction for PartialFunction {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn documentation(&self) -> &dyn FunctionDocumentation {
+        &self.own_docs
+    }
+
+    fn documentation_mut(&mut self) -> &mut dyn FunctionDocumentation {
+        &mut self.own_docs
+    }
+
+    fn return_type(&self) -> ValueType {
+        ValueType::Any
+    }
+
+    fn expected_arguments(&self) -> Vec<(Cow<'static, str>, FunctionArgument)> {
+        vec![(
+            Cow::Borrowed("arguments"),
+            FunctionArgument {
+                expected_type: ValueType::Any,
+                meta: FunctionArgumentType::Plural,
+            },
+        )]
+    }
+
+    fn clone_self(&self) -> Box<dyn ParserFunction> {
+        Box::new(self.clone())
+    }
+
+    fn call(
+        &self,
+        state: &mut State,
+        _reference: Option<&AssignmentTarget>,
+    ) -> Result<Value, Error> {
+        let mut arguments = self.fixed_arguments.clone();
+        if let Some(remaining) = state.get_variable("arguments") {
+            arguments.extend(remaining.clone().as_a::<Vec<Value>>()?);
+        }
+
+        state.call_function(&self.function, arguments, None)
+    }
+}
+
+impl PartialFunction {
+    /// Create a new partially-applied version of the named function
+    pub fn new(function: &str, fixed_arguments: Vec<Value>) -> Self {
+        let name = format!(
+            "partial({}, {})",
+            function,
+            Value::from(fixed_arguments.clone())
+        );
+        PartialFunction {
+            name,
+            function: function.to_string(),
+            fixed_arguments,
+            own_docs: UserFunctionDocumentation {
+                category: "User-Defined Functions".to_string(),
+                description: Some(format!("Partial application of {function}")),
+                ext_description: None,
+                examples: None,
+            },
+        }
+    }
+}
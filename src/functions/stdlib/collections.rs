@@ -1,10 +1,10 @@
 use crate::{
     define_stdfunction,
-    error::{ErrorDetails, WrapExternalError},
+    error::{ErrorDetails, WrapExternalError, WrapOption},
 };
 use polyvalue::{
     operations::{IndexingMutationExt, IndexingOperationExt},
-    types::{Array, Object},
+    types::{Array, Object, Range},
     InnerValue, Value, ValueType,
 };
 
@@ -108,6 +108,100 @@ define_stdfunction!(
     },
 );
 
+define_stdfunction!(
+    nth { input: Standard::Any, index: Standard::Int },
+    returns = Any,
+    docs = {
+        category: "Collections",
+        description: "Returns the element at the given index of an array or string",
+        ext_description: "
+            Negative indices count back from the end of the collection, so -1 is the last element.
+            Strings are indexed by character; all other values are coerced to an array.
+            If the index is out of range, an error is returned.
+        ",
+        examples: "
+            assert_eq(nth([1, 2, 3], 1),  2);
+            assert_eq(nth([1, 2, 3], -1), 3);
+            assert_eq(nth('test', -2),    's');
+
+            would_err('nth([1, 2, 3], 3)');  // Out of range
+            would_err('nth([1, 2, 3], -4)'); // Out of range
+        ",
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::input);
+        let index = required_arg!(state::index).as_a::<i64>()?;
+
+        let elements: Vec<Value> = if input.is_a(ValueType::String) {
+            input.to_string().chars().map(|c| Value::from(c.to_string())).collect()
+        } else {
+            input.as_a::<Vec<Value>>()?
+        };
+
+        let len = elements.len() as i64;
+        let position = if index < 0 { len + index } else { index };
+        if position < 0 || position >= len {
+            return oops!(Range { input: index.to_string() });
+        }
+
+        Ok(elements[position as usize].clone())
+    },
+);
+
+define_stdfunction!(
+    slice {
+        input: Standard::Any,
+        start: Optional::Int,
+        end: Optional::Int
+    },
+    returns = Any,
+    docs = {
+        category: "Collections",
+        description: "Returns the part of an array or string between two indices",
+        ext_description: "
+            The start index is inclusive, and the end index is exclusive.
+            Negative indices count back from the end of the collection, and omitted bounds default to the start and end.
+            Out of range bounds are clamped, so an empty result is returned instead of an error.
+            Strings are sliced by character; all other values are coerced to an array.
+        ",
+        examples: "
+            assert_eq(slice([1, 2, 3, 4], 1, 3),  [2, 3]);
+            assert_eq(slice([1, 2, 3, 4], -2),    [3, 4]);
+            assert_eq(slice([1, 2, 3, 4], 0, -1), [1, 2, 3]);
+            assert_eq(slice('hello', 1, 10),      'ello');
+            assert_eq(slice('hello', 4, 2),       '');
+        ",
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::input);
+        let is_string = input.is_a(ValueType::String);
+        let elements: Vec<Value> = if is_string {
+            input.to_string().chars().map(|c| Value::from(c.to_string())).collect()
+        } else {
+            input.as_a::<Vec<Value>>()?
+        };
+
+        let len = elements.len() as i64;
+        let clamp = |index: i64| if index < 0 { (len + index).max(0) } else { index.min(len) };
+
+        let start = match optional_arg!(state::start) {
+            Some(start) => clamp(start.as_a::<i64>()?),
+            None => 0,
+        };
+        let end = match optional_arg!(state::end) {
+            Some(end) => clamp(end.as_a::<i64>()?),
+            None => len,
+        };
+
+        let elements = elements[start as usize..end.max(start) as usize].to_vec();
+        if is_string {
+            Ok(Value::from(elements.iter().map(|c| c.to_string()).collect::<String>()))
+        } else {
+            Ok(Value::from(elements))
+        }
+    },
+);
+
 /**********************************************
  *
  * Array Manipulation Functions
@@ -438,6 +532,240 @@ define_stdfunction!(
     },
 );
 
+define_stdfunction!(
+    to_object { input: Standard::Any },
+    returns = Object,
+    docs = {
+        category: "Collections",
+        description: "Converts a value to an object",
+        ext_description: "
+            Equivalent to `input as object`: arrays become objects keyed by index, and other values become a single-entry object with the key 0.
+            Useful when a conversion needs to be passed by name to another function.
+        ",
+        examples: "
+            assert_eq(to_object([1, 2, 3]), [1, 2, 3] as object);
+            assert_eq(len(to_object([1, 2, 3])), 3);
+            assert_eq(to_object(5), 5 as object);
+            assert_eq(to_object({'a': 1}), {'a': 1});
+        ",
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::input);
+        Ok(input.as_type(ValueType::Object)?)
+    },
+);
+
+define_stdfunction!(
+    to_array { input: Standard::Any },
+    returns = Array,
+    docs = {
+        category: "Collections",
+        description: "Converts a value to an array",
+        ext_description: "
+            Equivalent to `input as array`: objects become an array of their values, ranges are expanded, and other values become a single-element array.
+            Useful when a conversion needs to be passed by name to another function.
+        ",
+        examples: "
+            assert_eq(to_array(5), [5]);
+            assert_eq(to_array({1: 2}), [2]);
+            assert_eq(to_array(1..3), [1, 2, 3]);
+            assert_eq(flat_map([1, 2], 'to_array'), [1, 2]);
+        ",
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::input);
+        Ok(input.as_type(ValueType::Array)?)
+    },
+);
+
+define_stdfunction!(
+    has_key { input: Standard::Object, key: Standard::Any },
+    returns = Bool,
+    docs = {
+        category: "Collections",
+        description: "Returns true if the given object contains a key",
+        ext_description: "
+            Keys are compared using the same rules as indexing into the object.
+        ",
+        examples: "
+            assert(has_key({'a': 1}, 'a'));
+            assert(!has_key({'a': 1}, 'b'));
+            assert(has_key({1: 'one'}, 1));
+        ",
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::input).as_a::<Object>()?;
+        let key = required_arg!(state::key);
+        Ok(Value::from(input.get(&key).is_some()))
+    },
+);
+
+define_stdfunction!(
+    has { input: Standard::Collection, key: Standard::Any },
+    returns = Bool,
+    docs = {
+        category: "Collections",
+        description: "Returns true if the given key or index exists in a collection",
+        ext_description: "
+            For objects, this checks for the key, like has_key.
+            For arrays and strings, this checks that the index is in range. Negative indices count back from the end.
+        ",
+        examples: "
+            assert(has({'a': 1}, 'a'));
+            assert(has([1, 2, 3], 2));
+            assert(has([1, 2, 3], -3));
+            assert(!has([1, 2, 3], 3));
+            assert(has('abc', 0));
+            assert(!has('', 0));
+        ",
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::input);
+        let key = required_arg!(state::key);
+        if input.is_a(ValueType::Object) {
+            return Ok(Value::from(input.as_a::<Object>()?.get(&key).is_some()));
+        }
+
+        let len = if input.is_a(ValueType::String) {
+            input.to_string().chars().count()
+        } else {
+            input.as_a::<Vec<Value>>()?.len()
+        } as i64;
+
+        let index = match key.as_a::<i64>() {
+            Ok(index) if key.is_a(ValueType::Int) => index,
+            _ => return Ok(Value::from(false)),
+        };
+        Ok(Value::from(-len <= index && index < len))
+    },
+);
+
+define_stdfunction!(
+    sorted_keys { input: Standard::Object },
+    returns = Array,
+    docs = {
+        category: "Collections",
+        description: "Returns an array of the keys of the given object, in sorted order",
+        ext_description: "
+            Equivalent to keys(input).sort().
+        ",
+        examples: "
+            assert_eq(sorted_keys({'b': 2, 'a': 1}), ['a', 'b']);
+            assert_eq(sorted_keys({}), []);
+        ",
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::input).as_a::<Object>()?;
+        let mut keys = input.keys().into_iter().cloned().collect::<Vec<_>>();
+        keys.sort();
+        Ok(Value::from(keys))
+    },
+);
+
+define_stdfunction!(
+    sorted_values { input: Standard::Object },
+    returns = Array,
+    docs = {
+        category: "Collections",
+        description: "Returns an array of the values of the given object, ordered by their keys",
+        ext_description: "
+            The values are sorted by their corresponding keys, not by value.
+            This keeps them in the same order as sorted_keys.
+        ",
+        examples: "
+            assert_eq(sorted_values({'b': 1, 'a': 2}), [2, 1]);
+            assert_eq(sorted_values({}), []);
+        ",
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::input).as_a::<Object>()?;
+        let mut entries = input.keys().into_iter().zip(input.values()).collect::<Vec<_>>();
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(Value::from(entries.into_iter().map(|(_, v)| v.clone()).collect::<Vec<_>>()))
+    },
+);
+
+define_stdfunction!(
+    sort_object { input: Standard::Object },
+    returns = Array,
+    docs = {
+        category: "Collections",
+        description: "Returns the entries of an object as [key, value] pairs, sorted by key",
+        ext_description: "
+            Objects do not keep their keys in any particular order, so the sorted entries are returned as an array.
+            Useful for output that is the same on every run; for loops over an object already visit its keys in sorted order.
+        ",
+        examples: "
+            o = sort_object({'b': 2, 'c': 3, 'a': 1})
+            assert_eq(o, [['a', 1], ['b', 2], ['c', 3]]);
+            assert_eq(for entry in o do entry[0], ['a', 'b', 'c']);
+        ",
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::input).as_a::<Object>()?;
+        let mut entries = input.keys().into_iter().cloned()
+            .zip(input.values().into_iter().cloned())
+            .collect::<Vec<_>>();
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(Value::from(
+            entries.into_iter().map(|(k, v)| Value::from(vec![k, v])).collect::<Vec<_>>()
+        ))
+    },
+);
+
+define_stdfunction!(
+    columns {
+        input: Standard::Array,
+        default: Optional::Any
+    },
+    returns = Object,
+    docs = {
+        category: "Collections",
+        description: "Transposes an array of objects into an object of arrays",
+        ext_description: "
+            Returns an object mapping each key found in the input records to an array of its values across all records.
+            Records missing a key will have the default value (nil if not specified) in that position.
+        ",
+        examples: "
+            assert_eq(
+                columns([{'a': 1, 'b': 2}, {'a': 3, 'b': 4}]),
+                {'a': [1, 3], 'b': [2, 4]}
+            );
+            assert_eq(
+                columns([{'a': 1}, {'b': 2}], 0),
+                {'a': [1, 0], 'b': [0, 2]}
+            );
+            assert_eq(columns([]), {});
+        ",
+    },
+    handler = (state, _reference) {
+        let records = required_arg!(state::input).as_a::<Vec<Value>>()?
+            .into_iter()
+            .map(|r| r.as_a::<Object>())
+            .collect::<Result<Vec<_>, _>>()?;
+        let default = optional_arg!(state::default).unwrap_or(Value::from(false));
+
+        let mut keys: Vec<Value> = vec![];
+        for record in records.iter() {
+            for key in record.keys() {
+                if !keys.contains(key) {
+                    keys.push(key.clone());
+                }
+            }
+        }
+
+        let result = keys.into_iter().map(|key| {
+            let column = records
+                .iter()
+                .map(|r| r.get(&key).cloned().unwrap_or(default.clone()))
+                .collect::<Vec<_>>();
+            (key, Value::from(column))
+        }).collect::<Vec<_>>();
+
+        Ok(Object::try_from(result)?.into())
+    },
+);
+
 /**********************************************
  *
  * Array Query Functions
@@ -445,46 +773,84 @@ define_stdfunction!(
  *********************************************/
 
 define_stdfunction!(
-    all { input: Standard::Array },
+    all {
+        input: Standard::Array,
+        predicate: Optional::String
+    },
     returns = Bool,
     docs = {
         category: "Collections",
         description: "Returns true if all elements of the given array are truthy",
         ext_description: "
             Returns true if all elements of the given array evaluate to true.
+            If a predicate function name is given, it is called on each element instead, stopping at the first falsy result.
             If the array is empty, true is returned.
         ",
         examples: "
             assert_eq(all([true, true, true]), true);
             assert_eq(all([0, 1, 2]), false);
             assert_eq(all([]), true);
+
+            is_positive(n) = n > 0
+            assert_eq(all([1, 2, 3], 'is_positive'), true);
+            assert_eq(all([1, -2, 3], 'is_positive'), false);
         ",
     },
     handler = (state, _reference) {
-        let input = required_arg!(state::input).as_a::<Array>()?;
-        Ok(Value::bool(input.iter().all(|v| v.is_truthy())))
+        let input = required_arg!(state::input).as_a::<Vec<Value>>()?;
+        match optional_arg!(state::predicate) {
+            Some(predicate) => {
+                let predicate = predicate.to_string();
+                for value in input {
+                    if !state.call_function(&predicate, vec![value], None)?.is_truthy() {
+                        return Ok(Value::bool(false));
+                    }
+                }
+                Ok(Value::bool(true))
+            }
+            None => Ok(Value::bool(input.iter().all(|v| v.is_truthy()))),
+        }
     },
 );
 
 define_stdfunction!(
-    any { input: Standard::Array },
+    any {
+        input: Standard::Array,
+        predicate: Optional::String
+    },
     returns = Bool,
     docs = {
         category: "Collections",
         description: "Returns true if any element of the given array is truthy",
         ext_description: "
             Returns true if any element of the given array evaluates to true.
+            If a predicate function name is given, it is called on each element instead, stopping at the first truthy result.
             If the array is empty, false is returned.
         ",
         examples: "
             assert_eq(any([true, true, true]), true);
             assert_eq(any([0, 1, 2]), true);
             assert_eq(any([]), false);
+
+            is_odd(n) = n % 2 == 1
+            assert_eq(any([2, 3, 4], 'is_odd'), true);
+            assert_eq(any([2, 4], 'is_odd'), false);
         ",
     },
     handler = (state, _reference) {
-        let input = required_arg!(state::input).as_a::<Array>()?;
-        Ok(Value::bool(input.iter().any(|v| v.is_truthy())))
+        let input = required_arg!(state::input).as_a::<Vec<Value>>()?;
+        match optional_arg!(state::predicate) {
+            Some(predicate) => {
+                let predicate = predicate.to_string();
+                for value in input {
+                    if state.call_function(&predicate, vec![value], None)?.is_truthy() {
+                        return Ok(Value::bool(true));
+                    }
+                }
+                Ok(Value::bool(false))
+            }
+            None => Ok(Value::bool(input.iter().any(|v| v.is_truthy()))),
+        }
     },
 );
 
@@ -541,6 +907,73 @@ define_stdfunction!(
     },
 );
 
+define_stdfunction!(
+    find_by {
+        input: Standard::Array,
+        predicate: Standard::String
+    },
+    returns = Any,
+    docs = {
+        category: "Collections",
+        description: "Returns the first element of the given array matching a predicate",
+        ext_description: "
+            Calls the named function on each element of the array in order, and returns the first element for which it returned a truthy value.
+            Elements after the first match are not checked. If no element matches, nil is returned.
+            Unlike find(), which searches for a value, this searches using a function.
+        ",
+        examples: "
+            is_even(n) = n % 2 == 0
+            assert_eq(find_by([1, 3, 4, 6], 'is_even'), 4);
+            assert_eq(find_by([1, 3], 'is_even'), nil);
+        ",
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::input).as_a::<Vec<Value>>()?;
+        let predicate = required_arg!(state::predicate).to_string();
+
+        for value in input {
+            if state.call_function(&predicate, vec![value.clone()], None)?.is_truthy() {
+                return Ok(value);
+            }
+        }
+
+        Ok(Value::from(false))
+    },
+);
+
+define_stdfunction!(
+    find_index {
+        input: Standard::Array,
+        predicate: Standard::String
+    },
+    returns = Int,
+    docs = {
+        category: "Collections",
+        description: "Returns the index of the first element of the given array matching a predicate",
+        ext_description: "
+            Calls the named function on each element of the array in order, and returns the index of the first element for which it returned a truthy value.
+            Elements after the first match are not checked. If no element matches, -1 is returned.
+        ",
+        examples: "
+            is_negative(n) = n < 0
+            assert_eq(find_index([1, -3, -4], 'is_negative'), 1);
+            assert_eq(find_index([1, 3], 'is_negative'), -1);
+        ",
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::input).as_a::<Vec<Value>>()?;
+        let predicate = required_arg!(state::predicate).to_string();
+
+        for (i, value) in input.into_iter().enumerate() {
+            if state.call_function(&predicate, vec![value], None)?.is_truthy() {
+                return Ok(Value::from(i as i64));
+            }
+        }
+
+        Ok(Value::from(-1))
+    },
+);
+
 /**********************************************
  *
  * Array Combinators
@@ -703,6 +1136,116 @@ define_stdfunction!(
     },
 );
 
+define_stdfunction!(
+    flat_map {
+        input: Standard::Array,
+        function: Standard::String
+    },
+    returns = Array,
+    docs = {
+        category: "Collections",
+        description: "Maps each element of an array to an array, and concatenates the results",
+        ext_description: "
+            Calls the named function on each element of the array in order, and joins the returned arrays into a single array.
+            Results that are not arrays are coerced to arrays first. Any error raised by the function is returned immediately.
+            Equivalent to flattening the mapped array, without building the intermediate result.
+        ",
+        examples: "
+            twice(n) = [n, n]
+            assert_eq(flat_map([1, 2, 3], 'twice'), [1, 1, 2, 2, 3, 3]);
+            assert_eq(flat_map([], 'twice'), []);
+        ",
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::input).as_a::<Vec<Value>>()?;
+        let function = required_arg!(state::function).to_string();
+
+        let mut result = vec![];
+        for value in input {
+            let mapped = state.call_function(&function, vec![value], None)?;
+            result.extend(mapped.as_a::<Vec<Value>>()?);
+        }
+
+        Ok(Value::from(result))
+    },
+);
+
+define_stdfunction!(
+    reduce {
+        input: Standard::Array,
+        function: Standard::String,
+        initial: Optional::Any
+    },
+    returns = Any,
+    docs = {
+        category: "Collections",
+        description: "Combines the elements of an array into a single value, from left to right",
+        ext_description: "
+            Calls the named function with the accumulator and each element in order, and uses the result as the next accumulator.
+            If no initial value is given, the first element is used, and an empty array is an error.
+        ",
+        examples: "
+            join_pair(a, b) = a + b
+            assert_eq(reduce([1, 2, 3], 'join_pair'), 6);
+            assert_eq(reduce(['a', 'b', 'c'], 'join_pair'), 'abc');
+            assert_eq(reduce([], 'join_pair', 10), 10);
+            would_err(\"reduce([], 'join_pair')\");
+        ",
+    },
+    handler = (state, _reference) {
+        let mut input = required_arg!(state::input).as_a::<Vec<Value>>()?.into_iter();
+        let function = required_arg!(state::function).to_string();
+
+        let mut accumulator = match optional_arg!(state::initial) {
+            Some(initial) => initial,
+            None => input.next().or_error(ErrorDetails::ArrayEmpty)?,
+        };
+        for value in input {
+            accumulator = state.call_function(&function, vec![accumulator, value], None)?;
+        }
+
+        Ok(accumulator)
+    },
+);
+
+define_stdfunction!(
+    reduce_right {
+        input: Standard::Array,
+        function: Standard::String,
+        initial: Optional::Any
+    },
+    returns = Any,
+    docs = {
+        category: "Collections",
+        description: "Combines the elements of an array into a single value, from right to left",
+        ext_description: "
+            Works like reduce(), but visits the elements starting from the end of the array.
+            The function is still called with the accumulator first, and the element second.
+            If no initial value is given, the last element is used, and an empty array is an error.
+        ",
+        examples: "
+            join_pair(a, b) = a + b
+            assert_eq(reduce_right(['a', 'b', 'c'], 'join_pair'), 'cba');
+            assert_eq(reduce_right(['a', 'b', 'c'], 'join_pair', '>'), '>cba');
+            assert_eq(reduce(['a', 'b', 'c'], 'join_pair', '>'), '>abc');
+        ",
+    },
+    handler = (state, _reference) {
+        let mut input = required_arg!(state::input).as_a::<Vec<Value>>()?.into_iter().rev();
+        let function = required_arg!(state::function).to_string();
+
+        let mut accumulator = match optional_arg!(state::initial) {
+            Some(initial) => initial,
+            None => input.next().or_error(ErrorDetails::ArrayEmpty)?,
+        };
+        for value in input {
+            accumulator = state.call_function(&function, vec![accumulator, value], None)?;
+        }
+
+        Ok(accumulator)
+    },
+);
+
 define_stdfunction!(
     zip {
         left: Standard::Array,
@@ -731,6 +1274,38 @@ define_stdfunction!(
     },
 );
 
+define_stdfunction!(
+    zip_all {
+        arrays: Standard::Array
+    },
+    returns = Array,
+    docs = {
+        category: "Collections",
+        description: "Zips an array of arrays into an array of tuples, and returns the result",
+        ext_description: "
+            A generalization of zip to any number of arrays; the nth tuple contains the nth element of each input array.
+            If the input arrays are of different lengths, the resulting array will have the length of the shortest input array.
+        ",
+        examples: "
+            assert_eq(zip_all([[1, 2, 3], [4, 5, 6], [7, 8, 9]]), [[1, 4, 7], [2, 5, 8], [3, 6, 9]]);
+            assert_eq(zip_all([[1, 2, 3], [4, 5], ['a', 'b', 'c', 'd']]), [[1, 4, 'a'], [2, 5, 'b']]);
+            assert_eq(zip_all([]), []);
+        ",
+    },
+    handler = (state, _reference) {
+        let arrays = required_arg!(state::arrays).as_a::<Vec<Value>>()?
+            .into_iter()
+            .map(|a| a.as_a::<Vec<Value>>())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let length = arrays.iter().map(|a| a.len()).min().unwrap_or(0);
+        let result = (0..length)
+            .map(|i| Value::from(arrays.iter().map(|a| a[i].clone()).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        Ok(Value::from(result))
+    },
+);
+
 define_stdfunction!(
     zop {
         left: Standard::Array,
@@ -759,6 +1334,40 @@ define_stdfunction!(
     },
 );
 
+define_stdfunction!(
+    object_from {
+        keys: Standard::Array,
+        values: Standard::Array,
+        default: Optional::Any
+    },
+    returns = Object,
+    docs = {
+        category: "Collections",
+        description: "Builds an object from an array of keys and an array of values",
+        ext_description: "
+            Unlike zop, every key appears in the result; keys without a matching value are given the default, or nil if none is given.
+            Values beyond the number of keys are ignored.
+            Will fail if any of the keys are invalid (collections cannot be used as object keys)
+        ",
+        examples: "
+            assert_eq(object_from(['a', 'b', 'c'], [1, 2], 0), {'a': 1, 'b': 2, 'c': 0});
+            assert_eq(object_from(['a', 'b'], [1]), {'a': 1, 'b': nil});
+            assert_eq(object_from(['a'], [1, 2, 3]), {'a': 1});
+        ",
+    },
+    handler = (state, _reference) {
+        let keys = required_arg!(state::keys).as_a::<Vec<Value>>()?;
+        let values = required_arg!(state::values).as_a::<Vec<Value>>()?;
+        let default = optional_arg!(state::default).unwrap_or(Value::from(false));
+
+        let mut values = values.into_iter();
+        let result = keys.into_iter()
+            .map(|key| (key, values.next().unwrap_or_else(|| default.clone())))
+            .collect::<Vec<(_, _)>>();
+        Ok(Value::from(Object::try_from(result)?))
+    },
+);
+
 define_stdfunction!(
     sort { input: Standard::Array },
     returns = Array,
@@ -804,3 +1413,154 @@ define_stdfunction!(
         Ok(result.into())
     },
 );
+
+define_stdfunction!(
+    min_by {
+        input: Standard::Array,
+        key_function: Standard::String
+    },
+    returns = Any,
+    docs = {
+        category: "Collections",
+        description: "Returns the element of the given array with the smallest key",
+        ext_description: "
+            Calls the named function on each element of the array, and returns the element for which it returned the smallest value.
+            If several elements share the smallest key, the first is returned.
+            An empty array is an error.
+        ",
+        examples: "
+            assert_eq(min_by(['ccc', 'a', 'bb'], 'len'), 'a');
+            assert_eq(min_by([[1, 2], [3], [4]], 'len'), [3]);
+        ",
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::input).as_a::<Vec<Value>>()?;
+        let key_function = required_arg!(state::key_function).to_string();
+
+        let mut result: Option<(Value, Value)> = None;
+        for value in input {
+            let key = state.call_function(&key_function, vec![value.clone()], None)?;
+            match &result {
+                Some((min, _)) if key >= *min => {}
+                _ => result = Some((key, value)),
+            }
+        }
+
+        result.map(|(_, value)| value).or_error(ErrorDetails::ArrayEmpty)
+    },
+);
+
+define_stdfunction!(
+    max_by {
+        input: Standard::Array,
+        key_function: Standard::String
+    },
+    returns = Any,
+    docs = {
+        category: "Collections",
+        description: "Returns the element of the given array with the largest key",
+        ext_description: "
+            Calls the named function on each element of the array, and returns the element for which it returned the largest value.
+            If several elements share the largest key, the first is returned.
+            An empty array is an error.
+        ",
+        examples: "
+            record_score(r) = r['score']
+            records = [{'name': 'a', 'score': 3}, {'name': 'b', 'score': 7}, {'name': 'c', 'score': 7}]
+            assert_eq(max_by(records, 'record_score')['name'], 'b');
+            assert( would_err(\"max_by([], 'len')\") );
+        ",
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::input).as_a::<Vec<Value>>()?;
+        let key_function = required_arg!(state::key_function).to_string();
+
+        let mut result: Option<(Value, Value)> = None;
+        for value in input {
+            let key = state.call_function(&key_function, vec![value.clone()], None)?;
+            match &result {
+                Some((max, _)) if key <= *max => {}
+                _ => result = Some((key, value)),
+            }
+        }
+
+        result.map(|(_, value)| value).or_error(ErrorDetails::ArrayEmpty)
+    },
+);
+
+/**********************************************
+ *
+ * Range Functions
+ *
+ *********************************************/
+
+define_stdfunction!(
+    range_contains { range: Standard::Range, value: Standard::Int },
+    returns = Bool,
+    docs = {
+        category: "Collections",
+        description: "Returns true if the given value is within a range",
+        ext_description: "
+            Ranges are inclusive, so both ends are contained in the range.
+            The range is not expanded into an array, so this is fast even for very large ranges.
+        ",
+        examples: "
+            assert(range_contains(1..10, 10));
+            assert(!range_contains(1..10, 11));
+            assert(range_contains(-1000000000..1000000000, 0));
+        ",
+    },
+    handler = (state, _reference) {
+        let range = required_arg!(state::range).as_a::<Range>()?.inner().clone();
+        let value = required_arg!(state::value).as_a::<i64>()?;
+        Ok(Value::from(range.contains(&value)))
+    },
+);
+
+define_stdfunction!(
+    range_length { range: Standard::Range },
+    returns = Int,
+    docs = {
+        category: "Collections",
+        description: "Returns the number of values in a range",
+        ext_description: "
+            Ranges are inclusive, so 1..3 has a length of 3.
+            The range is not expanded into an array, so this is fast even for very large ranges.
+        ",
+        examples: "
+            assert_eq(range_length(1..3), 3);
+            assert_eq(range_length(5..5), 1);
+            assert_eq(range_length(0..999999999), 1000000000);
+            assert(would_err('range_length(5..1)')); // Reversed ranges cannot be created
+        ",
+    },
+    handler = (state, _reference) {
+        let range = required_arg!(state::range).as_a::<Range>()?.inner().clone();
+        let length = *range.end() as i128 - *range.start() as i128 + 1;
+        Ok(Value::from(i64::try_from(length).unwrap_or(i64::MAX)))
+    },
+);
+
+define_stdfunction!(
+    range_overlaps { a: Standard::Range, b: Standard::Range },
+    returns = Bool,
+    docs = {
+        category: "Collections",
+        description: "Returns true if two ranges share at least one value",
+        ext_description: "
+            Ranges are inclusive, so ranges that share an end overlap, while adjacent ranges such as 1..3 and 4..6 do not.
+        ",
+        examples: "
+            assert(range_overlaps(1..5, 3..8));
+            assert(range_overlaps(1..3, 3..5));
+            assert(range_overlaps(1..3, 3..6));
+            assert(!range_overlaps(1..3, 4..6));
+            assert(range_overlaps(1..10, 4..6));
+        ",
+    },
+    handler = (state, _reference) {
+        let a = required_arg!(state::a).as_a::<Range>()?.inner().clone();
+        let b = required_arg!(state::b).as_a::<Range>()?.inner().clone();
+        Ok(Value::from(a.start() <= b.end() && b.start() <= a.end()))
+    },
+);
@@ -5,7 +5,10 @@ define_stddecorator!(
     usd { input: Numeric },
     docs = {
         description: "Interprets a number as a USD amount",
-        ext_description: "Includes a dollar sign and two decimal places.",
+        ext_description: "
+            Includes a dollar sign and two decimal places.
+            Like all currency decorators, it uses the decimal and thousands separators configured in the parser options.
+        ",
         examples: "
             assert_eq(
                 100 @usd,
@@ -13,9 +16,9 @@ define_stddecorator!(
             )
         "
     },
-    handler = (input) {
+    handler = (input, state) {
         let input = input.as_a::<Fixed>()?;
-        Ok(CurrencyInner::as_dollars(input).to_string())
+        Ok(state.number_format().apply(&CurrencyInner::as_dollars(input).to_string()))
     }
 );
 
@@ -31,9 +34,9 @@ define_stddecorator!(
             )
         "
     },
-    handler = (input) {
+    handler = (input, state) {
         let input = input.as_a::<Fixed>()?;
-        Ok(CurrencyInner::as_euros(input).to_string())
+        Ok(state.number_format().apply(&CurrencyInner::as_euros(input).to_string()))
     }
 );
 
@@ -49,9 +52,9 @@ define_stddecorator!(
             )
         "
     },
-    handler = (input) {
+    handler = (input, state) {
         let input = input.as_a::<Fixed>()?;
-        Ok(CurrencyInner::as_dollars(input).to_string())
+        Ok(state.number_format().apply(&CurrencyInner::as_dollars(input).to_string()))
     }
 );
 
@@ -67,9 +70,9 @@ define_stddecorator!(
             )
         "
     },
-    handler = (input) {
+    handler = (input, state) {
         let input = input.as_a::<Fixed>()?;
-        Ok(CurrencyInner::as_dollars(input).to_string())
+        Ok(state.number_format().apply(&CurrencyInner::as_dollars(input).to_string()))
     }
 );
 
@@ -85,9 +88,9 @@ define_stddecorator!(
             )
         "
     },
-    handler = (input) {
+    handler = (input, state) {
         let input = input.as_a::<Fixed>()?;
-        Ok(CurrencyInner::as_pounds(input).to_string())
+        Ok(state.number_format().apply(&CurrencyInner::as_pounds(input).to_string()))
     }
 );
 
@@ -103,9 +106,9 @@ define_stddecorator!(
             )
         "
     },
-    handler = (input) {
+    handler = (input, state) {
         let input = input.as_a::<Fixed>()?;
-        Ok(CurrencyInner::as_yen(input).to_string())
+        Ok(state.number_format().apply(&CurrencyInner::as_yen(input).to_string()))
     }
 );
 
@@ -121,9 +124,9 @@ define_stddecorator!(
             )
         "
     },
-    handler = (input) {
+    handler = (input, state) {
         let input = input.as_a::<Fixed>()?;
-        Ok(CurrencyInner::as_yuan(input).to_string())
+        Ok(state.number_format().apply(&CurrencyInner::as_yuan(input).to_string()))
     }
 );
 
@@ -139,9 +142,9 @@ define_stddecorator!(
             )
         "
     },
-    handler = (input) {
+    handler = (input, state) {
         let input = input.as_a::<Fixed>()?;
-        Ok(CurrencyInner::as_rupees(input).to_string())
+        Ok(state.number_format().apply(&CurrencyInner::as_rupees(input).to_string()))
     }
 );
 
@@ -157,8 +160,8 @@ define_stddecorator!(
             )
         "
     },
-    handler = (input) {
+    handler = (input, state) {
         let input = input.as_a::<Fixed>()?;
-        Ok(CurrencyInner::as_rubles(input).to_string())
+        Ok(state.number_format().apply(&CurrencyInner::as_rubles(input).to_string()))
     }
 );
@@ -107,8 +107,8 @@ define_stddecorator!(
             )
         "
     },
-    handler = (input) {
+    handler = (input, state) {
         let input = input.as_a::<f64>()?;
-        Ok(format!("{}%", input * 100.0))
+        Ok(state.number_format().apply(&format!("{}%", input * 100.0)))
     }
 );
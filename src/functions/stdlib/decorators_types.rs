@@ -121,9 +121,9 @@ define_stddecorator!(
             )
         "
     },
-    handler = (input) {
+    handler = (input, state) {
         let input = input.as_a::<Float>()?;
-        Ok(input.to_string())
+        Ok(state.number_format().apply(&input.to_string()))
     }
 );
 
@@ -139,9 +139,9 @@ define_stddecorator!(
             )
         "
     },
-    handler = (input) {
+    handler = (input, state) {
         let input = input.as_a::<I64>()?;
-        Ok(input.to_string())
+        Ok(state.number_format().apply(&input.to_string()))
     }
 );
 
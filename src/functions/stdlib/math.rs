@@ -2,7 +2,7 @@ use crate::{
     define_stdfunction,
     error::{ErrorDetails, WrapOption},
 };
-use polyvalue::{fpdec::Round, types::CurrencyInner, InnerValue, Value, ValueTrait};
+use polyvalue::{fpdec::Round, types::CurrencyInner, InnerValue, Value, ValueTrait, ValueType};
 
 define_stdfunction!(
     min {
@@ -69,18 +69,23 @@ define_stdfunction!(
         description: "Rounds a number up to the nearest whole number",
         ext_description: "
             The function will round the input number up to the nearest whole number.
-            If the input number is already a whole number, the function will return the input number.
+            Integers are returned unchanged; other numbers are converted to floats.
         ",
         examples: "
             assert_eq(
                 ceil(1.5),
                 2.0
             )
+            assert_eq(ceil(-1.5), -1.0)
+            assert_eq(ceil(5), 5)
         "
     },
     handler = (state, _reference) {
-        let value = required_arg!(state::value).as_a::<f64>()?;
-        Ok(value.ceil().into())
+        let value = required_arg!(state::value);
+        if value.is_a(ValueType::Int) {
+            return Ok(value);
+        }
+        Ok(value.as_a::<f64>()?.ceil().into())
     }
 );
 
@@ -94,18 +99,77 @@ define_stdfunction!(
         description: "Rounds a number down to the nearest whole number",
         ext_description: "
             The function will round the input number down to the nearest whole number.
-            If the input number is already a whole number, the function will return the input number.
+            Integers are returned unchanged; other numbers are converted to floats.
         ",
         examples: "
             assert_eq(
                 floor(1.5),
                 1.0
             )
+            assert_eq(floor(-1.5), -2.0)
+            assert_eq(floor(5), 5)
         "
     },
     handler = (state, _reference) {
-        let value = required_arg!(state::value).as_a::<f64>()?;
-        Ok(value.floor().into())
+        let value = required_arg!(state::value);
+        if value.is_a(ValueType::Int) {
+            return Ok(value);
+        }
+        Ok(value.as_a::<f64>()?.floor().into())
+    }
+);
+
+define_stdfunction!(
+    trunc {
+        value: Standard::Numeric
+    },
+    returns = Numeric,
+    docs = {
+        category: "Math",
+        description: "Removes the fractional part of a number",
+        ext_description: "
+            Rounds the input number towards zero, unlike floor which always rounds down.
+            Integers are returned unchanged; other numbers are converted to floats.
+        ",
+        examples: "
+            assert_eq(trunc(1.5), 1.0)
+            assert_eq(trunc(-1.5), -1.0)
+            assert_eq(trunc(5), 5)
+        "
+    },
+    handler = (state, _reference) {
+        let value = required_arg!(state::value);
+        if value.is_a(ValueType::Int) {
+            return Ok(value);
+        }
+        Ok(value.as_a::<f64>()?.trunc().into())
+    }
+);
+
+define_stdfunction!(
+    fract {
+        value: Standard::Numeric
+    },
+    returns = Numeric,
+    docs = {
+        category: "Math",
+        description: "Returns the fractional part of a number",
+        ext_description: "
+            The result has the same sign as the input, so that trunc(x) + fract(x) == x.
+            Integers always have a fractional part of 0, of the same type.
+        ",
+        examples: "
+            assert_eq(fract(1.25), 0.25)
+            assert_eq(fract(-1.25), -0.25)
+            assert_eq(fract(5), 0)
+        "
+    },
+    handler = (state, _reference) {
+        let value = required_arg!(state::value);
+        if value.is_a(ValueType::Int) {
+            return Ok(Value::from(0i64).as_type(value.own_type())?);
+        }
+        Ok(value.as_a::<f64>()?.fract().into())
     }
 );
 
@@ -172,12 +236,15 @@ define_stdfunction!(
         ext_description: "
             The function will round the input number to the nearest whole number.
             If the input number is already a whole number, the function will return the input number.
+            Integers are returned unchanged.
         ",
         examples: "
             assert_eq(
                 round(1.5),
                 2.0
             )
+            assert_eq(round(-1.5), -2.0)
+            assert_eq(round(5), 5)
         "
     },
     handler = (state, _reference) {
@@ -200,6 +267,7 @@ define_stdfunction!(
                 let n = n / 10.0_f64.powi(precision as i32);
                 Ok(Value::from(n))
             }
+            _ if value.is_a(ValueType::Int) => Ok(value.clone()),
             _ => oops!(
                 Internal {
                     msg: "Invalid argument type".to_string()
@@ -213,23 +281,27 @@ define_stdfunction!(
     log2 {
         value: Standard::Numeric
     },
-    returns = Numeric,
+    returns = Float,
     docs = {
         category: "Math",
         description: "Returns the base-2 logarithm of a number",
-        ext_description: "",
+        ext_description: "
+            The input is converted to a float, and must be greater than 0.
+        ",
         examples: "
             assert_eq(
                 log2(8),
-                3
+                3.0
             )
+            assert( would_err('log2(0)') )
         "
     },
     handler = (state, _reference) {
-        let value = required_arg!(state::value);
-        let type_name = value.own_type();
-        let value = value.as_a::<f64>()?;
-        Ok(Value::from(value.log2()).as_type(type_name)?)
+        let value = required_arg!(state::value).as_a::<f64>()?;
+        if value <= 0.0 {
+            return oops!(Range { input: value.to_string() });
+        }
+        Ok(Value::from(value.log2()))
     }
 );
 
@@ -261,23 +333,26 @@ define_stdfunction!(
     log10 {
         value: Standard::Numeric
     },
-    returns = Numeric,
+    returns = Float,
     docs = {
         category: "Math",
         description: "Returns the base-10 logarithm of a number",
-        ext_description: "",
+        ext_description: "
+            The input is converted to a float, and must be greater than 0.
+        ",
         examples: "
             assert_eq(
                 log10(100),
-                2
+                2.0
             )
         "
     },
     handler = (state, _reference) {
-        let value = required_arg!(state::value);
-        let type_name = value.own_type();
-        let value = value.as_a::<f64>()?;
-        Ok(Value::from(value.log10()).as_type(type_name)?)
+        let value = required_arg!(state::value).as_a::<f64>()?;
+        if value <= 0.0 {
+            return oops!(Range { input: value.to_string() });
+        }
+        Ok(Value::from(value.log10()))
     }
 );
 
@@ -285,11 +360,13 @@ define_stdfunction!(
     ln {
         value: Standard::Numeric
     },
-    returns = Numeric,
+    returns = Float,
     docs = {
         category: "Math",
         description: "Returns the natural logarithm of a number",
-        ext_description: "",
+        ext_description: "
+            The input is converted to a float, and must be greater than 0.
+        ",
         examples: "
             assert_eq(
                 ln(2.718281828459045),
@@ -298,10 +375,11 @@ define_stdfunction!(
         "
     },
     handler = (state, _reference) {
-        let value = required_arg!(state::value);
-        let type_name = value.own_type();
-        let value = value.as_a::<f64>()?;
-        Ok(Value::from(value.ln()).as_type(type_name)?)
+        let value = required_arg!(state::value).as_a::<f64>()?;
+        if value <= 0.0 {
+            return oops!(Range { input: value.to_string() });
+        }
+        Ok(Value::from(value.ln()))
     }
 );
 
@@ -310,21 +388,28 @@ define_stdfunction!(
         value: Standard::Numeric,
         base: Optional::Numeric
     },
-    returns = Numeric,
+    returns = Float,
     docs = {
         category: "Math",
         description: "Returns the logarithm of a number to a given base",
-        ext_description: "",
+        ext_description: "
+            The base defaults to 10. Both arguments are converted to floats and must be greater than 0, and the base cannot be 1.
+            Results are subject to floating point error, so compare them with a tolerance where exactness matters.
+        ",
         examples: "
-            assert_eq(
-                log(8, 2),
-                3.0
-            )
+            assert( abs(log(8, 2) - 3) < 0.000001 )
+            assert( abs(log(100) - 2) < 0.000001 )
+            assert( would_err('log(8, 1)') )
         "
     },
     handler = (state, _reference) {
         let value = required_arg!(state::value).as_a::<f64>()?;
         let base = optional_arg!(state::base).unwrap_or(10.into()).as_a::<f64>()?;
+        if value <= 0.0 {
+            return oops!(Range { input: value.to_string() });
+        } else if base <= 0.0 || base == 1.0 {
+            return oops!(Range { input: base.to_string() });
+        }
         Ok(value.log(base).into())
     }
 );
@@ -1,7 +1,11 @@
 use crate::{
     define_stdfunction,
     error::{ErrorDetails, WrapExternalError, WrapOption},
-    network::{request, resolve, ApiDefinition, ApiRegistry},
+    network::{
+        default_headers, download, merge_headers, ping, request, resolve, resolve_all,
+        set_default_headers, value_at_path, ApiDefinition, ApiRegistry, DEFAULT_TIMEOUT,
+    },
+    Error, State,
 };
 use polyvalue::{types::Object, Value};
 use serde_json::json;
@@ -15,7 +19,8 @@ use std::collections::HashMap;
 
 define_stdfunction!(
     resolve {
-        hostname: Standard::String
+        hostname: Standard::String,
+        ipv6: Optional::Bool
     },
     returns = String,
     docs = {
@@ -23,15 +28,79 @@ define_stdfunction!(
         description: "Resolves a hostname to an IP address",
         ext_description: "
             This function uses the system's DNS resolver to resolve a hostname to an IP address.
+            IPv4 addresses are preferred; if ipv6 is true, an IPv6 address is returned instead.
             If the hostname cannot be resolved, this function will return an error, or time out
         ",
         examples: "#skip
             resolve('example.com')
+            resolve('example.com', true)
         "
     },
     handler = (state, _reference) {
         let hostname = required_arg!(state::hostname).to_string();
-        Ok(resolve(&hostname).unwrap())
+        let ipv6 = optional_arg!(state::ipv6).map(|v| v.is_truthy()).unwrap_or(false);
+        resolve(&hostname, ipv6)
+    }
+);
+
+define_stdfunction!(
+    resolve_all {
+        hostname: Standard::String
+    },
+    returns = Array,
+    docs = {
+        category: "Network",
+        description: "Resolves a hostname to all of its IP addresses",
+        ext_description: "
+            This function uses the system's DNS resolver to find every IPv4 and IPv6 address for a hostname.
+            If the hostname cannot be resolved, this function will return an error, or time out
+        ",
+        examples: "#skip
+            addresses = resolve_all('example.com')
+            assert( addresses contains resolve('example.com') )
+        "
+    },
+    handler = (state, _reference) {
+        let hostname = required_arg!(state::hostname).to_string();
+        let addresses = resolve_all(&hostname)?;
+        Ok(Value::from(addresses.iter().map(|addr| Value::from(addr.to_string())).collect::<Vec<_>>()))
+    }
+);
+
+define_stdfunction!(
+    ping {
+        host: Standard::String,
+        timeout_ms: Standard::Int,
+        port: Optional::Int
+    },
+    returns = Object,
+    docs = {
+        category: "Network",
+        description: "Checks whether a host can be reached",
+        ext_description: "
+            Attempts a TCP connection to the host on the given port (80 by default), giving up after timeout_ms milliseconds.
+            Returns an object with the keys reachable, and rtt_ms - the time taken to connect, or nil if the host could not be reached.
+            Hosts that cannot be resolved are reported as unreachable.
+        ",
+        examples: "#skip
+            result = ping('example.com', 1000)
+            assert(result['reachable'])
+            ping('example.com', 1000, 443)['rtt_ms']
+        "
+    },
+    handler = (state, _reference) {
+        let host = required_arg!(state::host).to_string();
+        let timeout_ms = required_arg!(state::timeout_ms).as_a::<i64>()?.max(0);
+        let port = optional_arg!(state::port).map(|p| p.as_a::<i64>()).transpose()?.unwrap_or(80);
+        let port = u16::try_from(port).ok().or_error(ErrorDetails::ValueFormat {
+            expected_format: "port between 0 and 65535".to_string()
+        })?;
+
+        let rtt = ping(&host, port, std::time::Duration::from_millis(timeout_ms as u64));
+        Ok(Value::try_from(vec![
+            (Value::from("reachable"), Value::from(rtt.is_some())),
+            (Value::from("rtt_ms"), rtt.map_or(Value::from(false), |t| Value::from(t.as_secs_f64() * 1000.0))),
+        ])?)
     }
 );
 
@@ -46,6 +115,7 @@ define_stdfunction!(
         description: "Performs an HTTP GET request",
         ext_description: "
             This function performs an HTTP GET request to the specified URL.
+            Headers set with set_default_headers are included, unless overridden by the given headers.
             If the request fails, this function will return an error or time out
         ",
         examples: "#skip
@@ -61,7 +131,8 @@ define_stdfunction!(
         let url = required_arg!(state::url).to_string();
         let headers = optional_arg!(state::headers).unwrap_or(Value::from(Object::default())).as_a::<Object>()?;
         let headers = headers.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<HashMap<_, _>>();
-        request(&url, None, headers).without_context()
+        let headers = merge_headers(default_headers(state), headers);
+        request(&url, None, headers, DEFAULT_TIMEOUT).without_context()
     }
 );
 
@@ -77,6 +148,7 @@ define_stdfunction!(
         description: "Performs an HTTP POST request",
         ext_description: "
             This function performs an HTTP POST request to the specified URL.
+            Headers set with set_default_headers are included, unless overridden by the given headers.
             If the request fails, this function will return an error or time out
         ",
         examples: "#skip
@@ -92,7 +164,111 @@ define_stdfunction!(
         let body = required_arg!(state::body).to_string();
         let headers = optional_arg!(state::headers).unwrap_or(Value::from(Object::default())).as_a::<Object>()?;
         let headers = headers.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<HashMap<_, _>>();
-        request(&url, Some(body), headers).without_context()
+        let headers = merge_headers(default_headers(state), headers);
+        request(&url, Some(body), headers, DEFAULT_TIMEOUT).without_context()
+    }
+);
+
+define_stdfunction!(
+    download {
+        url: Standard::String,
+        path: Standard::String,
+        headers: Optional::Object
+    },
+    returns = Int,
+    docs = {
+        category: "Network",
+        description: "Saves the response of an HTTP GET request to a file",
+        ext_description: "
+            The response is written to the file as it arrives, so large files are never held in memory.
+            Headers set with set_default_headers are included, unless overridden by the given headers.
+            Returns the number of bytes written. Fails if the server responds with an error status, or the file cannot be written.
+        ",
+        examples: "#skip
+            bytes = download('https://jsonplaceholder.typicode.com/users', 'users.json')
+            assert(bytes > 0)
+        "
+    },
+    handler = (state, _reference) {
+        let url = required_arg!(state::url).to_string();
+        let path = required_arg!(state::path).to_string();
+        let headers = optional_arg!(state::headers).unwrap_or(Value::from(Object::default())).as_a::<Object>()?;
+        let headers = headers.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<HashMap<_, _>>();
+        let headers = merge_headers(default_headers(state), headers);
+
+        let written = download(&url, std::path::Path::new(&path), headers, || state.check_timer()).without_context()?;
+        Ok(Value::from(written as i64))
+    }
+);
+
+define_stdfunction!(
+    set_default_headers {
+        headers: Standard::Object
+    },
+    returns = Object,
+    docs = {
+        category: "Network",
+        description: "Sets the headers sent with every HTTP request",
+        ext_description: "
+            The headers are included in every get and post request, such as for authentication or a User-Agent.
+            Headers given to get or post take precedence over these. Header names are case-insensitive.
+            Replaces any previously set default headers; pass an empty object to remove them.
+        ",
+        examples: "
+            assert_eq(
+                set_default_headers({'User-Agent': 'lavendeux'})['User-Agent'],
+                'lavendeux'
+            )
+            set_default_headers({})
+        "
+    },
+    handler = (state, _reference) {
+        let headers = required_arg!(state::headers).as_a::<Object>()?;
+        set_default_headers(state, headers.clone());
+        Ok(Value::from(headers))
+    }
+);
+
+define_stdfunction!(
+    url_parse {
+        url: Standard::String
+    },
+    returns = Object,
+    docs = {
+        category: "Network",
+        description: "Splits a URL into its components",
+        ext_description: "
+            Returns an object with the keys scheme, host, port, path, query and fragment.
+            The port is the scheme's default if not given; host, port, query and fragment are nil if absent.
+            Use parse_query to break down the query string.
+        ",
+        examples: "
+            url = url_parse('https://example.com:8080/api/users?id=5&sort=asc#top')
+            assert_eq(url['scheme'], 'https')
+            assert_eq(url['host'], 'example.com')
+            assert_eq(url['port'], 8080)
+            assert_eq(url['path'], '/api/users')
+            assert_eq(url['query'], 'id=5&sort=asc')
+            assert_eq(url['fragment'], 'top')
+            assert_eq(url_parse('http://example.com')['port'], 80)
+            assert( would_err('url_parse(\"not a url\")') )
+        "
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::url).to_string();
+        let url = url::Url::parse(&input).ok().or_error(ErrorDetails::ValueFormat {
+            expected_format: "<scheme>://<host>[:<port>][/<path>][?<query>][#<fragment>]".to_string()
+        })?;
+
+        let optional = |s: Option<&str>| s.map_or(Value::from(false), Value::from);
+        Ok(Value::try_from(vec![
+            (Value::from("scheme"), Value::from(url.scheme())),
+            (Value::from("host"), optional(url.host_str())),
+            (Value::from("port"), url.port_or_known_default().map_or(Value::from(false), |p| Value::from(p as i64))),
+            (Value::from("path"), Value::from(url.path())),
+            (Value::from("query"), optional(url.query())),
+            (Value::from("fragment"), optional(url.fragment())),
+        ])?)
     }
 );
 
@@ -113,12 +289,17 @@ define_stdfunction!(
         description: "Registers an API",
         ext_description: "
             This function registers an API with the system. The API can then be used to make requests to the specified endpoint.
-            The endpoint can be a string, or an object with the properties [ base_url, headers, description, examples, auth_key]
+            The endpoint can be a string, or an object with the properties [ base_url, additional_headers, description, examples, auth_key, timeout ]
+            additional_headers are sent with every request to the API, and timeout is the number of milliseconds to wait for a response
             Use the 'api_get' and 'api_post' functions to make requests to the registered API
         ",
         examples: "
             api_add('ipify', 'https://api.ipify.org')
             assert( api_list() contains 'ipify' )
+
+            api_add('slow', {'base_url': 'https://example.com', 'timeout': 5000})
+            assert_eq( api_all()['slow']['timeout'], 5000 )
+            api_rem('slow')
         "
     },
     handler = (state, _reference) {
@@ -286,6 +467,37 @@ define_stdfunction!(
     }
 );
 
+define_stdfunction!(
+    llm {
+        prompt: Standard::String,
+        options: Optional::Object
+    },
+    returns = String,
+    docs = {
+        category: "API",
+        description: "Sends a prompt to an OpenAI-compatible chat completion API",
+        ext_description: "
+            Sends the prompt to a registered API, and returns the text of the reply.
+            The options object can contain the following keys, which default to the behaviour of chatgpt():
+            - api: Name of the registered API to use ('chatgpt')
+            - model: Model to request ('gpt-3.5-turbo')
+            - system: System prompt sent before the user's prompt
+            - temperature: Sampling temperature; the API's default is used if not given
+            - response_path: Dot-separated path to the reply in the response ('choices.0.message.content')
+            Use api_add to register other endpoints, and api_key to authenticate with them.
+        ",
+        examples: "#skip
+            api_add('local', 'http://localhost:8080/v1/chat/completions')
+            llm('What is the meaning of life?', {'api': 'local', 'model': 'llama3', 'temperature': 0.2})
+        "
+    },
+    handler = (state, _reference) {
+        let prompt = required_arg!(state::prompt).to_string();
+        let options = optional_arg!(state::options).unwrap_or(Value::from(Object::default())).as_a::<Object>()?;
+        llm_request(state, prompt, options)
+    }
+);
+
 define_stdfunction!(
     chatgpt {
         prompt: Standard::String
@@ -296,6 +508,7 @@ define_stdfunction!(
         description: "Performs a request to the ChatGPT API",
         ext_description: "
             This function performs a request to the ChatGPT 3.5 API, using the specified prompt.
+            Equivalent to llm(prompt); use llm to change the model or other options.
         ",
         examples: "#skip
             api_key('chatgpt', 'my_super_secret_api_key')
@@ -304,52 +517,60 @@ define_stdfunction!(
     },
     handler = (state, _reference) {
         let prompt = required_arg!(state::prompt).to_string();
-        let registry = ApiRegistry::new(state);
-        let api = registry.get("chatgpt").or_error(ErrorDetails::Custom {
-            msg: "API 'chatgpt' not found".to_string(),
-        })?;
-
-        if api.auth_key.is_none() {
-            return oops!(ValueFormat {
-                expected_format: "API key for chatgpt is not set. You can set one with api_key('chatgpt', '<key>')".to_string()
-            });
-        }
-
-        use serde::{Deserialize, Serialize};
-        #[derive(Serialize, Deserialize)]
-        struct GPTMsg {
-            role: String,
-            content: String,
-        }
-        #[derive(Serialize, Deserialize)]
-        struct GPTQuery {
-            model: String,
-            messages: Vec<GPTMsg>,
-        }
-
-        let query = GPTQuery {
-            model: "gpt-3.5-turbo".to_string(),
-            messages: vec![
-                GPTMsg {
-                    role: "system".to_string(),
-                    content:
-                        "You are a chatbot that must respond in concise, single-line messages."
-                            .to_string(),
-                },
-                GPTMsg {
-                    role: "user".to_string(),
-                    content: prompt,
-                },
-            ],
-        };
-        let query = serde_json::to_string(&query)?;
-
-        let result = api
-            .call(Some(&query), None, Default::default())?.to_string();
-
-        let json = json!(result);
-        let result = json["choices"][0]["message"]["content"].clone();
-
-        Ok(Value::from(result.to_string()))
+        llm_request(state, prompt, Object::default())
     }
 );
+
+/// Sends a prompt to a chat completion API, as described by the llm() options
+/// Shared by llm and chatgpt, so that neither is affected by functions of the same name in an imported namespace
+fn llm_request(state: &mut State, prompt: String, options: Object) -> Result<Value, Error> {
+    let option = |key: &str| options.get(&Value::from(key)).cloned();
+
+    let name = option("api").map_or("chatgpt".to_string(), |v| v.to_string());
+    let model = option("model").map_or("gpt-3.5-turbo".to_string(), |v| v.to_string());
+    let system = option("system").map_or(
+        "You are a chatbot that must respond in concise, single-line messages.".to_string(),
+        |v| v.to_string(),
+    );
+    let response_path =
+        option("response_path").map_or("choices.0.message.content".to_string(), |v| v.to_string());
+
+    let registry = ApiRegistry::new(state);
+    let api = registry.get(&name).or_error(ErrorDetails::Custom {
+        msg: format!("API '{}' not found", name),
+    })?;
+
+    if name == "chatgpt" && api.auth_key.is_none() {
+        return oops!(ValueFormat {
+            expected_format:
+                "API key for chatgpt is not set. You can set one with api_key('chatgpt', '<key>')"
+                    .to_string()
+        });
+    }
+
+    let mut query = json!({
+        "model": model,
+        "messages": [
+            { "role": "system", "content": system },
+            { "role": "user", "content": prompt },
+        ],
+    });
+    if let Some(temperature) = option("temperature") {
+        query["temperature"] = json!(temperature.as_a::<f64>()?);
+    }
+
+    let headers = HashMap::from([("Content-Type".to_string(), "application/json".to_string())]);
+    let mut result = api.call(None, Some(query.to_string()), headers)?;
+    if let Ok(json) = serde_json::from_str::<serde_json::Value>(&result.to_string()) {
+        result = Value::try_from(json)?;
+    }
+
+    let result = value_at_path(result, &response_path).or_error(ErrorDetails::Custom {
+        msg: format!(
+            "Response from '{}' has no value at '{}'",
+            name, response_path
+        ),
+    })?;
+
+    Ok(Value::from(result.to_string()))
+}
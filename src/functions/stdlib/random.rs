@@ -108,8 +108,7 @@ define_stdfunction!(
         }
 
         use rand::seq::SliceRandom;
-        let mut rng = rand::thread_rng();
-        Ok(options.choose(&mut rng).unwrap().to_string().into())
+        Ok(options.choose(state.rng()).unwrap().to_string().into())
     }
 );
 
@@ -137,9 +136,137 @@ define_stdfunction!(
 
         if let Some(range) = optional_arg!(state::range) {
             let range = range.as_a::<Range>()?.inner().clone();
-            Ok(rand::thread_rng().gen_range(range).into())
+            Ok(state.rng().gen_range(range).into())
         } else {
-            Ok(rand::random::<f64>().into())
+            Ok(state.rng().gen::<f64>().into())
         }
     }
 );
+
+define_stdfunction!(
+    random_int {
+        min: Standard::Int,
+        max: Standard::Int
+    },
+    returns = Int,
+    docs = {
+        category: "Random",
+        description: "Returns a random integer between min and max, inclusive",
+        ext_description: "
+            Uses a uniform distribution. Raises an error if min is greater than max.
+        ",
+        examples: "
+            r = random_int(1, 6)
+            assert( r >= 1 && r <= 6 )
+            assert_eq( random_int(3, 3), 3 )
+            assert( would_err('random_int(6, 1)') )
+        "
+    },
+    handler = (state, _reference) {
+        use rand::Rng;
+
+        let min = required_arg!(state::min).as_a::<i64>()?;
+        let max = required_arg!(state::max).as_a::<i64>()?;
+        if min > max {
+            return oops!(RangeStartGT {
+                start: min.to_string(),
+                end: max.to_string()
+            });
+        }
+
+        Ok(state.rng().gen_range(min..=max).into())
+    }
+);
+
+define_stdfunction!(
+    random_float {
+        min: Standard::Numeric,
+        max: Standard::Numeric
+    },
+    returns = Float,
+    docs = {
+        category: "Random",
+        description: "Returns a random float between min and max",
+        ext_description: "
+            Uses a uniform distribution; the result can be equal to min, but is always less than max, unless they are equal.
+            Raises an error if min is greater than max.
+        ",
+        examples: "
+            r = random_float(0, 0.5)
+            assert( r >= 0 && r < 0.5 )
+            assert( would_err('random_float(1, 0)') )
+        "
+    },
+    handler = (state, _reference) {
+        use rand::Rng;
+
+        let min = required_arg!(state::min).as_a::<f64>()?;
+        let max = required_arg!(state::max).as_a::<f64>()?;
+        if min > max {
+            return oops!(RangeStartGT {
+                start: min.to_string(),
+                end: max.to_string()
+            });
+        } else if min == max {
+            return Ok(min.into());
+        }
+
+        Ok(state.rng().gen_range(min..max).into())
+    }
+);
+
+define_stdfunction!(
+    random_bool {
+        probability: Optional::Numeric
+    },
+    returns = Bool,
+    docs = {
+        category: "Random",
+        description: "Returns true with the given probability",
+        ext_description: "
+            The probability must be between 0 and 1, and defaults to 0.5.
+        ",
+        examples: "
+            assert_eq( random_bool(1), true )
+            assert_eq( random_bool(0), false )
+            assert( would_err('random_bool(2)') )
+        "
+    },
+    handler = (state, _reference) {
+        use rand::Rng;
+
+        let probability = optional_arg!(state::probability).unwrap_or(Value::from(0.5)).as_a::<f64>()?;
+        if !(0.0..=1.0).contains(&probability) {
+            return oops!(Range { input: probability.to_string() });
+        }
+
+        Ok(state.rng().gen_bool(probability).into())
+    }
+);
+
+define_stdfunction!(
+    seed_random {
+        seed: Standard::Int
+    },
+    returns = Int,
+    docs = {
+        category: "Random",
+        description: "Seeds the random number generator",
+        ext_description: "
+            After seeding, the random functions return the same sequence of values every time.
+            The seed lasts for the lifetime of the parser state; without a seed, values are drawn from entropy.
+            Returns the seed.
+        ",
+        examples: "
+            seed_random(42)
+            a = [random_int(0, 1000), random_float(0, 1), rand()]
+            seed_random(42)
+            assert_eq( a, [random_int(0, 1000), random_float(0, 1), rand()] )
+        "
+    },
+    handler = (state, _reference) {
+        let seed = required_arg!(state::seed);
+        state.seed_random(seed.as_a::<i64>()? as u64);
+        Ok(seed)
+    }
+);
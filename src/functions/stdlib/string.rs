@@ -1,5 +1,9 @@
-use crate::define_stdfunction;
-use polyvalue::{Value, ValueType};
+use crate::{define_stdfunction, number_format::group_digits};
+use polyvalue::{
+    fpdec::Round,
+    types::{Fixed, Object},
+    InnerValue, Value, ValueType,
+};
 
 /**********************************************
  *
@@ -57,6 +61,56 @@ define_stdfunction!(
     },
 );
 
+define_stdfunction!(
+    to_bytes { s: Standard::String },
+    returns = Array,
+    docs = {
+        category: "String",
+        description: "Returns the UTF-8 encoding of a string, as an array of u8 bytes",
+        ext_description: "
+            This is the complement of from_bytes(); Output from one is valid input for the other.
+            The result is the same as a byte string literal, such as b'abc'
+        ",
+        examples: "
+            assert_eq([104u8, 105u8], to_bytes('hi'))
+            assert_eq(b'caf\\xC3\\xA9', to_bytes('café'))
+        "
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::s).to_string();
+        Ok(Value::array(input.bytes().map(|b| Value::from(polyvalue::types::U8::new(b))).collect()))
+    },
+);
+
+define_stdfunction!(
+    from_bytes { bytes: Standard::Array },
+    returns = String,
+    docs = {
+        category: "String",
+        description: "Decodes an array of bytes as a UTF-8 string",
+        ext_description: "
+            Each element must be an integer between 0 and 255.
+            Raises an error if the bytes are not valid UTF-8.
+        ",
+        examples: "
+            assert_eq('hi', from_bytes(b'hi'))
+            assert_eq('hi', from_bytes([104, 105]))
+            assert( would_err('from_bytes([255])') )
+        "
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::bytes).as_a::<Vec<Value>>()?;
+        let bytes = input.into_iter().map(|b| {
+            let b = b.as_a::<i64>()?;
+            u8::try_from(b).map_err(|_| crate::error::ErrorDetails::ValueFormat {
+                expected_format: "array of integers between 0 and 255".to_string()
+            }.into())
+        }).collect::<Result<Vec<u8>, crate::Error>>()?;
+
+        Ok(Value::from(String::from_utf8(bytes)?))
+    },
+);
+
 /**********************************************
  *
  * String Manipulation
@@ -96,6 +150,66 @@ define_stdfunction!(
     },
 );
 
+define_stdfunction!(
+    capitalize { s: Standard::String },
+    returns = String,
+    docs = {
+        category: "String",
+        description: "Converts the first character of a string to uppercase, and the rest to lowercase.",
+        ext_description: "
+            This function is locale-insensitive and will handle all Unicode characters.
+            Some characters become several when uppercased, such as 'ß' to 'SS', or 'ﬀ' to 'FF'.
+        ",
+        examples: "
+            assert_eq('Hello', capitalize('hELLO'))
+            assert_eq('SSe', capitalize('ßE'))
+            assert_eq('FFoo', capitalize('ﬀoo'))
+            assert_eq('', capitalize(''))
+        "
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::s).to_string();
+        let mut chars = input.chars();
+        let output = match chars.next() {
+            Some(first) => first.to_uppercase().collect::<String>() + &chars.as_str().to_lowercase(),
+            None => String::new(),
+        };
+        Ok(Value::from(output))
+    },
+);
+
+define_stdfunction!(
+    swapcase { s: Standard::String },
+    returns = String,
+    docs = {
+        category: "String",
+        description: "Converts uppercase characters in a string to lowercase, and lowercase characters to uppercase.",
+        ext_description: "
+            This function is locale-insensitive and will handle all Unicode characters.
+            Characters without case are unchanged. Some characters become several when uppercased, such as 'ß' to 'SS'.
+        ",
+        examples: "
+            assert_eq('hELLO wORLD 1', swapcase('Hello World 1'))
+            assert_eq('sTRASSE', swapcase('Straße'))
+            assert_eq('FF', swapcase('ﬀ'))
+        "
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::s).to_string();
+        let mut output = String::with_capacity(input.len());
+        for c in input.chars() {
+            if c.is_uppercase() {
+                output.extend(c.to_lowercase());
+            } else if c.is_lowercase() {
+                output.extend(c.to_uppercase());
+            } else {
+                output.push(c);
+            }
+        }
+        Ok(Value::from(output))
+    },
+);
+
 define_stdfunction!(
     trim { s: Standard::String },
     returns = String,
@@ -182,12 +296,29 @@ define_stdfunction!(
         ext_description: "This function is locale-insensitive and will handle all Unicode characters.",
         examples: "
             assert_eq('hellohellohello', repeat('hello', 3))
+            assert_eq('', repeat('hello', 0))
+            assert( would_err('repeat(\"x\", -1)') )
+            assert( would_err('repeat(\"x\", 9223372036854775807)') )
         "
     },
     handler = (state, _reference) {
+        // Guards against allocating enormous strings
+        const MAX_LENGTH: usize = 64 * 1024 * 1024;
+
         let input = required_arg!(state::s).to_string();
-        let n = required_arg!(state::n).as_a::<i32>()?;
-        Ok(Value::from(input.repeat(n as usize)))
+        let n = required_arg!(state::n).as_a::<i64>()?;
+        if n < 0 {
+            return oops!(Custom {
+                msg: "repeat() expected a non-negative count".to_string()
+            });
+        }
+
+        match usize::try_from(n).ok().and_then(|n| input.len().checked_mul(n)) {
+            Some(length) if length <= MAX_LENGTH => Ok(Value::from(input.repeat(n as usize))),
+            _ => oops!(Custom {
+                msg: format!("repeat() cannot produce a string longer than {MAX_LENGTH} bytes")
+            }),
+        }
     },
 );
 
@@ -211,6 +342,125 @@ define_stdfunction!(
     },
 );
 
+define_stdfunction!(
+    lines {
+        s: Standard::String
+    },
+    returns = Array,
+    docs = {
+        category: "String",
+        description: "Splits a string into its individual lines.",
+        ext_description: "
+            Lines may end in either \\n or \\r\\n, and both can be mixed in the same string; the line endings are removed.
+            A final line ending does not produce an empty line at the end. A lone \\r is not treated as a line ending.
+        ",
+        examples: "
+            assert_eq(['a', 'b', 'c'], lines('a\\nb\\r\\nc\\n'))
+            assert_eq(['a', '', 'b'], lines('a\\n\\nb'))
+            assert_eq([], lines(''))
+        "
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::s).to_string();
+        let lines: Vec<Value> = input.lines().map(Value::from).collect();
+        Ok(Value::from(lines))
+    },
+);
+
+define_stdfunction!(
+    for_each_line {
+        s: Standard::String,
+        function: Standard::String
+    },
+    returns = Int,
+    docs = {
+        category: "String",
+        description: "Calls a function on each line of a string, and returns the number of lines.",
+        ext_description: "
+            Lines are split in the same way as lines(), but each is passed to the named function as it is found, without building an array of all the lines.
+            The function's results are discarded, and any error it raises is returned immediately.
+        ",
+        examples: "
+            count_line(line) = assign_global('line_total', global('line_total') + len(line))
+            assign_global('line_total', 0)
+            assert_eq(3, for_each_line('ab\\nc\\r\\ndef', 'count_line'))
+            assert_eq(6, global('line_total'))
+        "
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::s).to_string();
+        let function = required_arg!(state::function).to_string();
+
+        let mut count = 0i64;
+        for line in input.lines() {
+            state.call_function(&function, vec![Value::from(line)], None)?;
+            count += 1;
+        }
+        Ok(Value::from(count))
+    },
+);
+
+define_stdfunction!(
+    count_chars {
+        s: Standard::String
+    },
+    returns = I64,
+    docs = {
+        category: "String",
+        description: "Returns the number of characters in a string.",
+        ext_description: "Counts Unicode characters rather than bytes, so 'é' counts as one character.",
+        examples: "
+            assert_eq(5, count_chars('héllo'))
+            assert_eq(0, count_chars(''))
+        "
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::s).to_string();
+        Ok(Value::from(input.chars().count() as i64))
+    },
+);
+
+define_stdfunction!(
+    count_words {
+        s: Standard::String
+    },
+    returns = I64,
+    docs = {
+        category: "String",
+        description: "Returns the number of words in a string.",
+        ext_description: "Words are separated by any amount of whitespace, including line endings.",
+        examples: "
+            assert_eq(3, count_words('  the quick\\n fox '))
+            assert_eq(0, count_words(''))
+        "
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::s).to_string();
+        Ok(Value::from(input.split_whitespace().count() as i64))
+    },
+);
+
+define_stdfunction!(
+    count_lines {
+        s: Standard::String
+    },
+    returns = I64,
+    docs = {
+        category: "String",
+        description: "Returns the number of lines in a string.",
+        ext_description: "Lines are counted in the same way as lines(), so a final line ending does not add an empty line.",
+        examples: "
+            assert_eq(2, count_lines('a\\r\\nb\\n'))
+            assert_eq(1, count_lines('a'))
+            assert_eq(0, count_lines(''))
+        "
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::s).to_string();
+        Ok(Value::from(input.lines().count() as i64))
+    },
+);
+
 define_stdfunction!(
     escape {
         s: Standard::String
@@ -303,6 +553,129 @@ define_stdfunction!(
     },
 );
 
+define_stdfunction!(
+    indent {
+        s: Standard::String,
+        prefix: Standard::String
+    },
+    returns = String,
+    docs = {
+        category: "String",
+        description: "Adds a prefix to the start of each line in a string.",
+        ext_description: "
+            Lines that are empty or contain only whitespace are left unchanged.
+            Line endings are preserved.
+        ",
+        examples: "
+            assert_eq('    a\\n\\n    b', indent('a\\n\\nb', '    '))
+            assert_eq('> a\\r\\n> b', indent('a\\r\\nb', '> '))
+        "
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::s).to_string();
+        let prefix = required_arg!(state::prefix).to_string();
+
+        let mut output = String::with_capacity(input.len());
+        for line in input.split_inclusive('\n') {
+            if !line.trim().is_empty() {
+                output.push_str(&prefix);
+            }
+            output.push_str(line);
+        }
+        Ok(Value::from(output))
+    },
+);
+
+define_stdfunction!(
+    dedent {
+        s: Standard::String
+    },
+    returns = String,
+    docs = {
+        category: "String",
+        description: "Removes the longest common leading whitespace from each line in a string.",
+        ext_description: "
+            Lines that contain only whitespace are ignored when finding the common prefix, and are emptied.
+            Tabs and spaces are compared exactly, so a tab is not the same as any number of spaces.
+            Line endings are preserved.
+        ",
+        examples: "
+            assert_eq('a\\n  b\\nc', dedent('    a\\n      b\\n    c'))
+            assert_eq(' a\\nb', dedent('\\t  a\\n\\t b'))
+            assert_eq('  a\\n\\tb', dedent('  a\\n\\tb'))
+        "
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::s).to_string();
+        let lines = input.split_inclusive('\n').collect::<Vec<_>>();
+
+        let mut common: Option<&str> = None;
+        for line in lines.iter().filter(|l| !l.trim().is_empty()) {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            common = Some(match common {
+                None => indent,
+                Some(common) => {
+                    let len = common.chars().zip(indent.chars())
+                        .take_while(|(a, b)| a == b)
+                        .map(|(a, _)| a.len_utf8())
+                        .sum();
+                    &common[..len]
+                }
+            });
+        }
+        let common = common.unwrap_or_default();
+
+        let mut output = String::with_capacity(input.len());
+        for line in lines {
+            if line.trim().is_empty() {
+                output.push_str(&line[line.trim_end_matches(['\r', '\n']).len()..]);
+            } else {
+                output.push_str(&line[common.len()..]);
+            }
+        }
+        Ok(Value::from(output))
+    },
+);
+
+define_stdfunction!(
+    truncate {
+        s: Standard::String,
+        length: Standard::I64,
+        suffix: Optional::String
+    },
+    returns = String,
+    docs = {
+        category: "String",
+        description: "Shortens a string to a maximum length, ending it with a suffix if it was cut.",
+        ext_description: "
+            Strings no longer than the maximum are returned unchanged.
+            Otherwise, the string is cut so that the result, including the suffix, is exactly the maximum length.
+            The suffix defaults to '…', and lengths are counted in characters rather than bytes.
+        ",
+        examples: "
+            assert_eq('hello', truncate('hello', 5))
+            assert_eq('hell…', truncate('hello world', 5))
+            assert_eq('he...', truncate('hello world', 5, '...'))
+            assert_eq('..', truncate('hello world', 2, '...'))
+            assert_eq('héll…', truncate('héllo wörld', 5))
+        "
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::s).to_string();
+        let length = required_arg!(state::length).as_a::<i64>()?.max(0) as usize;
+        let suffix = optional_arg!(state::suffix).map(|s| s.to_string()).unwrap_or("…".to_string());
+
+        if input.chars().count() <= length {
+            return Ok(Value::from(input));
+        }
+
+        // A suffix longer than the limit is itself cut down to size
+        let kept = length.saturating_sub(suffix.chars().count());
+        let output = input.chars().take(kept).chain(suffix.chars()).take(length).collect::<String>();
+        Ok(Value::from(output))
+    },
+);
+
 /**********************************************
  *
  * String Formatting
@@ -342,6 +715,147 @@ define_stdfunction!(
     },
 );
 
+define_stdfunction!(
+    format_number {
+        value: Standard::Numeric,
+        options: Optional::Object
+    },
+    returns = String,
+    docs = {
+        category: "String",
+        description: "Formats a number using the given options.",
+        ext_description: "
+            The options object can contain the following keys, all of which are optional:
+            - 'thousands': separator placed between groups of 3 digits, which can be any string; digits are not grouped if omitted
+            - 'decimal': separator placed before the fractional part, '.' by default
+            - 'decimals': number of decimal places to round to; fixed-point and integer values are rounded exactly
+            - 'sign': if true, positive numbers are given a '+' sign
+            - 'prefix' and 'suffix': text placed around the number, after the sign
+        ",
+        examples: "
+            assert_eq('1,234,567', format_number(1234567, {'thousands': ','}))
+            assert_eq('3.14', format_number(3.14159, {'decimals': 2}))
+            assert_eq('-1.234,5', format_number(-1234.5, {'thousands': '.', 'decimal': ','}))
+            assert_eq('+$1,234.50', format_number(1234.5, {'thousands': ',', 'decimals': 2, 'sign': true, 'prefix': '$'}))
+            assert_eq('1\\u{202F}000', format_number(1000, {'thousands': '\\u{202F}'}))
+            assert_eq('12345678901234567.89', format_number(12345678901234567.886d, {'decimals': 2}))
+            assert_eq('5.000', format_number(5, {'decimals': 3}))
+        "
+    },
+    handler = (state, _reference) {
+        let value = required_arg!(state::value);
+        let options = optional_arg!(state::options).unwrap_or(Value::from(Object::default())).as_a::<Object>()?;
+        let option = |key: &str| options.get(&Value::from(key)).map(|v| v.to_string());
+
+        let decimals = match options.get(&Value::from("decimals")) {
+            Some(decimals) => Some(decimals.as_a::<i64>()?.clamp(0, i8::MAX as i64) as usize),
+            None => None,
+        };
+
+        // Floats are formatted as floats; anything else is rounded as a fixed-point value, so no precision is lost
+        let text = match (value.inner(), decimals) {
+            (InnerValue::Float(n), Some(decimals)) => format!("{:.*}", decimals, n.inner()),
+            (InnerValue::Float(n), None) => n.inner().to_string(),
+            (_, Some(decimals)) => {
+                let n = value.as_a::<Fixed>()?.inner().clone().round(decimals as i8);
+                with_decimals(&n.to_string(), decimals)
+            }
+            (_, None) => value.as_a::<Fixed>()?.inner().to_string(),
+        };
+
+        let (sign, text) = match text.strip_prefix('-') {
+            Some(text) => ("-", text),
+            None if options.get(&Value::from("sign")).is_some_and(|v| v.is_truthy()) => ("+", text.as_str()),
+            None => ("", text.as_str()),
+        };
+
+        let (integer, fraction) = match text.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (text, None),
+        };
+
+        let mut output = format!("{sign}{}", option("prefix").unwrap_or_default());
+        match option("thousands").filter(|s| !s.is_empty()) {
+            Some(separator) => output.push_str(&group_digits(integer, &separator, 3)),
+            None => output.push_str(integer),
+        }
+        if let Some(fraction) = fraction {
+            output.push_str(&option("decimal").unwrap_or(".".to_string()));
+            output.push_str(fraction);
+        }
+        output.push_str(&option("suffix").unwrap_or_default());
+
+        Ok(Value::from(output))
+    },
+);
+
+define_stdfunction!(
+    parse_int {
+        s: Standard::String,
+        separator: Optional::String
+    },
+    returns = I64,
+    docs = {
+        category: "String",
+        description: "Parses a string as an integer.",
+        ext_description: "
+            Surrounding whitespace is ignored.
+            If a separator is given, every occurrence of it is removed before parsing, so that grouped numbers like '100,000' can be read.
+            By default nothing is removed, and an invalid number is an error.
+        ",
+        examples: "
+            assert_eq(42, parse_int(' 42 '))
+            assert_eq(-100000, parse_int('-100,000', ','))
+            assert_eq(1000000, parse_int('1 000 000', ' '))
+            would_err(\"parse_int('100,000')\")
+        "
+    },
+    handler = (state, _reference) {
+        let mut input = required_arg!(state::s).to_string();
+        if let Some(separator) = optional_arg!(state::separator) {
+            input = input.replace(&separator.to_string(), "");
+        }
+
+        match input.trim().parse::<i64>() {
+            Ok(n) => Ok(Value::from(n)),
+            Err(_) => oops!(ValueFormat { expected_format: "an integer".to_string() }),
+        }
+    },
+);
+
+define_stdfunction!(
+    parse_float {
+        s: Standard::String,
+        separator: Optional::String
+    },
+    returns = Float,
+    docs = {
+        category: "String",
+        description: "Parses a string as a floating-point number.",
+        ext_description: "
+            Surrounding whitespace is ignored, and '.' is the decimal separator.
+            If a separator is given, every occurrence of it is removed before parsing, so that grouped numbers like '1,234.5' can be read.
+            By default nothing is removed, and an invalid number is an error.
+        ",
+        examples: "
+            assert_eq(2.5, parse_float('2.5'))
+            assert_eq(1234.5, parse_float('1,234.5', ','))
+            would_err(\"parse_float('1,234.5')\")
+        "
+    },
+    handler = (state, _reference) {
+        let mut input = required_arg!(state::s).to_string();
+        if let Some(separator) = optional_arg!(state::separator) {
+            input = input.replace(&separator.to_string(), "");
+        }
+
+        match input.trim().parse::<f64>() {
+            Ok(n) => Ok(Value::from(n)),
+            Err(_) => oops!(ValueFormat { expected_format: "a number".to_string() }),
+        }
+    },
+);
+
 define_stdfunction!(
     prettyjson { s: Standard::Object },
     returns = String,
@@ -392,10 +906,70 @@ define_stdfunction!(
     },
 );
 
+define_stdfunction!(
+    join_format {
+        parts: Standard::Any,
+        joiner: Standard::String,
+        template: Standard::String
+    },
+    returns = String,
+    docs = {
+        category: "String",
+        description: "Formats each value using a template, and concatenates the results.",
+        ext_description: "
+            Each value replaces the {} in the template.
+            If a value is an array, its elements replace successive {}s instead; any {}s left over are kept as-is.
+            Objects are treated as an array of [key, value] pairs, sorted by key.
+            The formatted parts are separated by the joiner.
+        ",
+        examples: "
+            assert_eq('a=1, b=2', join_format({'a': 1, 'b': 2}, ', ', '{}={}'))
+            assert_eq('<1><2><3>', join_format([1, 2, 3], '', '<{}>'))
+            assert_eq('{}-b', join_format([['{}', 'b']], ',', '{}-{}'))
+        "
+    },
+    handler = (state, _reference) {
+        let parts = required_arg!(state::parts);
+        let joiner = required_arg!(state::joiner).to_string();
+        let template = required_arg!(state::template).to_string();
+
+        let parts = if parts.is_a(ValueType::Object) {
+            let parts = parts.as_a::<Object>()?;
+            let mut entries = parts.keys().into_iter().zip(parts.values()).collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            entries.into_iter().map(|(k, v)| Value::from(vec![k.clone(), v.clone()])).collect()
+        } else {
+            parts.as_a::<Vec<Value>>()?
+        };
+
+        // Placeholders are found once, so values containing {} are never substituted into
+        let pieces = template.split("{}").collect::<Vec<_>>();
+        let parts = parts.into_iter().map(|part| {
+            let args = if part.is_a(ValueType::Array) {
+                part.as_a::<Vec<Value>>()?
+            } else {
+                vec![part]
+            };
+
+            let mut result = pieces[0].to_string();
+            for (i, piece) in pieces.iter().enumerate().skip(1) {
+                match args.get(i - 1) {
+                    Some(arg) => result.push_str(&arg.to_string()),
+                    None => result.push_str("{}"),
+                }
+                result.push_str(piece);
+            }
+            Ok(result)
+        }).collect::<Result<Vec<_>, crate::Error>>()?;
+
+        Ok(Value::from(parts.join(&joiner)))
+    },
+);
+
 /**********************************************
  *
  * String Encoding
- * urlencode, urldecode, atob, btoa
+ * urlencode, urldecode, query strings, atob, btoa, msgpack, xml
  *********************************************/
 
 #[cfg(feature = "encoding-functions")]
@@ -434,6 +1008,81 @@ define_stdfunction!(
     },
 );
 
+#[cfg(feature = "encoding-functions")]
+define_stdfunction!(
+    parse_query { s: Standard::String },
+    returns = Object,
+    docs = {
+        category: "String",
+        description: "Parses a URL query string into an object",
+        ext_description: "
+            Keys and values are URL-decoded, and '+' is read as a space. A leading '?' is ignored.
+            Keys that appear more than once map to an array of their values.
+        ",
+        examples: "
+            assert_eq({'a': '1', 'b': 'hello world'}, parse_query('?a=1&b=hello%20world'))
+            assert_eq({'tag': ['x', 'y'], 'flag': ''}, parse_query('tag=x&tag=y&flag'))
+        "
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::s).to_string();
+
+        let mut entries: Vec<(String, Vec<Value>)> = vec![];
+        for pair in input.trim_start_matches('?').split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let key = urlencoding::decode(&key.replace('+', " "))?.into_owned();
+            let value = Value::from(urlencoding::decode(&value.replace('+', " "))?.into_owned());
+
+            match entries.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, values)) => values.push(value),
+                None => entries.push((key, vec![value])),
+            }
+        }
+
+        let entries = entries.into_iter().map(|(key, mut values)| {
+            let value = if values.len() == 1 { values.remove(0) } else { Value::from(values) };
+            (Value::from(key), value)
+        }).collect::<Vec<_>>();
+        Ok(Value::try_from(entries)?)
+    },
+);
+
+#[cfg(feature = "encoding-functions")]
+define_stdfunction!(
+    build_query { input: Standard::Object },
+    returns = String,
+    docs = {
+        category: "String",
+        description: "Builds a URL query string from an object",
+        ext_description: "
+            Keys and values are URL-encoded. Array values produce the key once for each element.
+        ",
+        examples: "
+            assert_eq('q=hello%20world', build_query({'q': 'hello world'}))
+            assert_eq('tag=x&tag=y', build_query({'tag': ['x', 'y']}))
+        "
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::input).as_a::<Object>()?;
+
+        let mut pairs = vec![];
+        for (key, value) in input.keys().into_iter().zip(input.values()) {
+            let key = urlencoding::encode(&key.to_string()).into_owned();
+            let values = if value.is_a(ValueType::Array) {
+                value.as_a::<Vec<Value>>()?
+            } else {
+                vec![value.clone()]
+            };
+
+            for value in values {
+                pairs.push(format!("{key}={}", urlencoding::encode(&value.to_string())));
+            }
+        }
+
+        Ok(Value::from(pairs.join("&")))
+    },
+);
+
 #[cfg(feature = "encoding-functions")]
 define_stdfunction!(
     base64_encode { s: Standard::String },
@@ -486,6 +1135,247 @@ define_stdfunction!(
     },
 );
 
+#[cfg(feature = "encoding-functions")]
+define_stdfunction!(
+    to_msgpack { v: Standard::Any },
+    returns = String,
+    docs = {
+        category: "String",
+        description: "Encodes a value as MessagePack, returned as a base64 string",
+        ext_description: "
+            Integers keep their sign, but not their size; fixed-point and currency values are stored as floats.
+            Ranges are stored as arrays, and objects as maps, with keys of any type.
+        ",
+        examples: "
+            assert_eq('kgEC', to_msgpack([1, 2]))
+            assert_eq([1, 'a'], from_msgpack(to_msgpack([1, 'a'])))
+        "
+    },
+    handler = (state, _reference) {
+        use polyvalue::{InnerValue, ValueTrait};
+        fn encode(value: &Value) -> Result<rmpv::Value, crate::Error> {
+            Ok(match value.own_type() {
+                ValueType::Bool => rmpv::Value::from(value.is_truthy()),
+                ValueType::String => rmpv::Value::from(value.to_string()),
+                ValueType::Array | ValueType::Range => rmpv::Value::Array(
+                    value.as_a::<Vec<Value>>()?.iter().map(encode).collect::<Result<_, _>>()?
+                ),
+                ValueType::Object => {
+                    let object = value.as_a::<Object>()?;
+                    rmpv::Value::Map(
+                        object.keys().into_iter().zip(object.values())
+                            .map(|(k, v)| Ok((encode(k)?, encode(v)?)))
+                            .collect::<Result<_, crate::Error>>()?
+                    )
+                },
+                _ => match value.inner() {
+                    InnerValue::U8(v) => rmpv::Value::from(v.inner().to_owned()),
+                    InnerValue::U16(v) => rmpv::Value::from(v.inner().to_owned()),
+                    InnerValue::U32(v) => rmpv::Value::from(v.inner().to_owned()),
+                    InnerValue::U64(v) => rmpv::Value::from(v.inner().to_owned()),
+                    _ if value.is_a(ValueType::Int) => rmpv::Value::from(value.as_a::<i64>()?),
+                    _ => rmpv::Value::from(value.as_a::<f64>()?),
+                }
+            })
+        }
+
+        let input = required_arg!(state::v);
+        let mut bytes = vec![];
+        rmpv::encode::write_value(&mut bytes, &encode(&input)?).map_err(|e| crate::error::ErrorDetails::Custom {
+            msg: e.to_string()
+        })?;
+
+        use base64::{engine::general_purpose, Engine as _};
+        Ok(Value::from(general_purpose::STANDARD.encode(bytes)))
+    },
+);
+
+#[cfg(feature = "encoding-functions")]
+define_stdfunction!(
+    from_msgpack { s: Standard::String },
+    returns = Any,
+    docs = {
+        category: "String",
+        description: "Decodes a base64 string of MessagePack data into a value",
+        ext_description: "
+            Integers that do not fit in an i64 become u64, and binary data becomes an array of integers.
+            Raises an error if the data is not valid MessagePack.
+        ",
+        examples: "
+            assert_eq({'a': 1}, from_msgpack('gaFhAQ=='))
+            assert( would_err('from_msgpack(\"not msgpack\")') )
+        "
+    },
+    handler = (state, _reference) {
+        fn decode(value: rmpv::Value) -> Result<Value, crate::Error> {
+            Ok(match value {
+                rmpv::Value::Nil => Value::from(false),
+                rmpv::Value::Boolean(b) => Value::from(b),
+                rmpv::Value::Integer(i) => match i.as_i64() {
+                    Some(i) => Value::from(i),
+                    None => Value::from(i.as_u64().unwrap_or_default()),
+                },
+                rmpv::Value::F32(f) => Value::from(f as f64),
+                rmpv::Value::F64(f) => Value::from(f),
+                rmpv::Value::String(s) => Value::from(s.into_str().unwrap_or_default()),
+                rmpv::Value::Binary(bytes) => Value::from(
+                    bytes.into_iter().map(|b| Value::from(b as i64)).collect::<Vec<_>>()
+                ),
+                rmpv::Value::Array(values) => Value::from(
+                    values.into_iter().map(decode).collect::<Result<Vec<_>, _>>()?
+                ),
+                rmpv::Value::Map(pairs) => Value::try_from(
+                    pairs.into_iter()
+                        .map(|(k, v)| Ok((decode(k)?, decode(v)?)))
+                        .collect::<Result<Vec<_>, crate::Error>>()?
+                )?,
+                rmpv::Value::Ext(..) => return oops!(ValueFormat {
+                    expected_format: "msgpack without extension types".to_string()
+                }),
+            })
+        }
+
+        let input = required_arg!(state::s).to_string();
+
+        use base64::{engine::general_purpose, Engine as _};
+        let bytes = general_purpose::STANDARD.decode(input).ok();
+        let value = bytes.and_then(|bytes| rmpv::decode::read_value(&mut bytes.as_slice()).ok());
+        match value {
+            Some(value) => decode(value),
+            None => oops!(ValueFormat {
+                expected_format: "base64 encoded msgpack".to_string()
+            }),
+        }
+    },
+);
+
+#[cfg(feature = "encoding-functions")]
+define_stdfunction!(
+    from_xml { s: Standard::String },
+    returns = Object,
+    docs = {
+        category: "String",
+        description: "Parses an XML document into nested objects",
+        ext_description: "
+            Each element becomes an object with the keys 'name', 'attributes' and 'children'.
+            'attributes' is an object of the element's attributes, and 'children' is an array of its child elements and text, in order.
+            Text is included as strings, with surrounding whitespace removed; whitespace-only text and comments are skipped.
+            Raises an error if the document is not valid XML.
+        ",
+        examples: "
+            doc = from_xml('<a id=\"1\"><b>hi</b> there</a>')
+            assert_eq(doc['name'], 'a')
+            assert_eq(doc['attributes'], {'id': '1'})
+            assert_eq(doc['children'][0]['children'], ['hi'])
+            assert_eq(doc['children'][1], 'there')
+        "
+    },
+    handler = (state, _reference) {
+        fn element(node: roxmltree::Node) -> Result<Value, crate::Error> {
+            let attributes = node.attributes()
+                .map(|a| (Value::from(a.name()), Value::from(a.value())))
+                .collect::<Vec<_>>();
+
+            let mut children = vec![];
+            for child in node.children() {
+                if child.is_element() {
+                    children.push(element(child)?);
+                } else if child.is_text() {
+                    let text = child.text().unwrap_or_default().trim();
+                    if !text.is_empty() {
+                        children.push(Value::from(text));
+                    }
+                }
+            }
+
+            Ok(Value::try_from(vec![
+                (Value::from("name"), Value::from(node.tag_name().name())),
+                (Value::from("attributes"), Value::try_from(attributes)?),
+                (Value::from("children"), Value::from(children)),
+            ])?)
+        }
+
+        let input = required_arg!(state::s).to_string();
+        match roxmltree::Document::parse(&input) {
+            Ok(document) => element(document.root_element()),
+            Err(_) => oops!(ValueFormat {
+                expected_format: "xml".to_string()
+            }),
+        }
+    },
+);
+
+#[cfg(feature = "encoding-functions")]
+define_stdfunction!(
+    to_xml { v: Standard::Object },
+    returns = String,
+    docs = {
+        category: "String",
+        description: "Converts nested objects into an XML document",
+        ext_description: "
+            The reverse of from_xml; each element is an object with the keys 'name', and optionally 'attributes' and 'children'.
+            Text in 'children' and attribute values are escaped.
+        ",
+        examples: "
+            assert_eq(
+                to_xml({'name': 'a', 'attributes': {'id': 1}, 'children': ['x < y', {'name': 'b'}]}),
+                '<a id=\"1\">x &lt; y<b/></a>'
+            )
+        "
+    },
+    handler = (state, _reference) {
+        fn escape(text: &str) -> String {
+            text.replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('"', "&quot;")
+        }
+
+        fn element(value: &Value, output: &mut String) -> Result<(), crate::Error> {
+            if value.is_a(ValueType::String) {
+                output.push_str(&escape(&value.to_string()));
+                return Ok(());
+            }
+
+            let object = value.as_a::<Object>()?;
+            let name = match object.get(&Value::from("name")) {
+                Some(name) => name.to_string(),
+                None => return oops!(ValueFormat {
+                    expected_format: "{name: string, attributes: object, children: array}".to_string()
+                }),
+            };
+
+            output.push_str(&format!("<{name}"));
+            if let Some(attributes) = object.get(&Value::from("attributes")) {
+                let attributes = attributes.as_a::<Object>()?;
+                for (k, v) in attributes.keys().into_iter().zip(attributes.values()) {
+                    output.push_str(&format!(" {k}=\"{}\"", escape(&v.to_string())));
+                }
+            }
+
+            let children = match object.get(&Value::from("children")) {
+                Some(children) => children.as_a::<Vec<Value>>()?,
+                None => vec![],
+            };
+            if children.is_empty() {
+                output.push_str("/>");
+            } else {
+                output.push('>');
+                for child in &children {
+                    element(child, output)?;
+                }
+                output.push_str(&format!("</{name}>"));
+            }
+            Ok(())
+        }
+
+        let input = required_arg!(state::v);
+        let mut output = String::new();
+        element(&input, &mut output)?;
+        Ok(Value::from(output))
+    },
+);
+
 define_stdfunction!(
     from_json {
         s: Standard::String
@@ -526,3 +1416,14 @@ define_stdfunction!(
         Ok(Value::from(input))
     },
 );
+
+/// Pads or truncates the fractional part of a formatted number to the given number of digits
+fn with_decimals(text: &str, decimals: usize) -> String {
+    let (integer, fraction) = text.split_once('.').unwrap_or((text, ""));
+    if decimals == 0 {
+        return integer.to_string();
+    }
+
+    let fraction = &fraction[..fraction.len().min(decimals)];
+    format!("{integer}.{fraction:0<decimals$}")
+}
@@ -2,10 +2,15 @@ use crate::{
     define_stdfunction,
     documentation::{DocumentationTemplate, MarkdownFormatter},
     error::{ErrorDetails, WrapOption},
+    functions::{ComposedFunction, NamespacedFunction, ParserFunction, PartialFunction},
     syntax_tree::traits::NodeExt,
-    Lavendeux,
+    Lavendeux, State,
+};
+use polyvalue::{
+    operations::{BooleanOperation, BooleanOperationExt},
+    types::Object,
+    Value, ValueType,
 };
-use polyvalue::{types::Object, Value};
 
 /**********************************************
  *
@@ -42,6 +47,101 @@ define_stdfunction!(
     },
 );
 
+define_stdfunction!(
+    compose {
+        functions: Plural::String
+    },
+    returns = String,
+
+    docs = {
+        category: "System",
+        description: "Combines the named functions into a new function, applied right-to-left",
+        ext_description: "
+            Creates a function that calls the last function given, then passes the result to the one before it, and so on; compose('f', 'g')(x) is f(g(x)).
+            The new function takes a single argument, and is named after the call that created it.
+            Its name is returned, and can be passed to call_function, or any function that accepts a function name.
+        ",
+        examples: "
+            double(x) = x * 2
+            inc(x) = x + 1
+            assert_eq(call_function(compose('double', 'inc'), [3]), 8)
+        ",
+    },
+    handler = (state, _reference) {
+        let functions = required_arg!(state::functions).as_a::<Vec<Value>>()?;
+        let functions = functions.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+        let function = ComposedFunction::compose(functions);
+        let name = function.name().to_string();
+        state.register_function(function)?;
+
+        Ok(Value::from(name))
+    },
+);
+
+define_stdfunction!(
+    pipe_fns {
+        functions: Plural::String
+    },
+    returns = String,
+
+    docs = {
+        category: "System",
+        description: "Combines the named functions into a new function, applied left-to-right",
+        ext_description: "
+            Creates a function that calls the first function given, then passes the result to the next, and so on; pipe_fns('f', 'g')(x) is g(f(x)).
+            The new function takes a single argument, and is named after the call that created it.
+            Its name is returned, and can be passed to call_function, or any function that accepts a function name.
+        ",
+        examples: "
+            double(x) = x * 2
+            inc(x) = x + 1
+            assert_eq(call_function(pipe_fns('double', 'inc'), [3]), 7)
+        ",
+    },
+    handler = (state, _reference) {
+        let functions = required_arg!(state::functions).as_a::<Vec<Value>>()?;
+        let functions = functions.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+        let function = ComposedFunction::pipe(functions);
+        let name = function.name().to_string();
+        state.register_function(function)?;
+
+        Ok(Value::from(name))
+    },
+);
+
+define_stdfunction!(
+    partial {
+        function: Standard::String,
+        fixed_arguments: Standard::Array
+    },
+    returns = String,
+
+    docs = {
+        category: "System",
+        description: "Creates a new function with some arguments already applied",
+        ext_description: "
+            Creates a function that calls the named function with the fixed arguments, followed by any arguments given to it.
+            The new function is named after the call that created it.
+            Its name is returned, and can be passed to call_function, or any function that accepts a function name.
+        ",
+        examples: "
+            add_values(a, b) = a + b
+            add_ten = partial('add_values', [10])
+            assert_eq(call_function(add_ten, [5]), 15)
+        ",
+    },
+    handler = (state, _reference) {
+        let function = required_arg!(state::function).to_string();
+        let fixed_arguments = required_arg!(state::fixed_arguments).as_a::<Vec<Value>>()?;
+
+        let function = PartialFunction::new(&function, fixed_arguments);
+        let name = function.name().to_string();
+        state.register_function(function)?;
+
+        Ok(Value::from(name))
+    },
+);
+
 define_stdfunction!(
     eval {
         expression: Standard::String
@@ -64,27 +164,52 @@ define_stdfunction!(
     handler = (state, _reference) {
         let expression = required_arg!(state::expression).to_string();
 
-        state.scope_into()?;
-        state.lock_scope();
-        let res = Lavendeux::eval(&expression, state).map(|n| n.evaluate(state));
-
-        let mut values = match res {
-            Ok(r) => {
-                match r {
-                    Ok(v) => v,
-                    Err(e) => {
-                        state.scope_out();
-                        return Err(e);
-                    }
-                }
-            },
-            Err(e) => {
-                state.scope_out();
-                return Err(e);
-            }
-        };
+        state.scope_into_eval()?;
+        let res = Lavendeux::eval(&expression, state).and_then(|n| n.evaluate(state));
+        state.scope_out_eval();
+
+        let mut values = res?;
+        if values.len() == 1 {
+            values = values.as_a::<Vec<Value>>()?.into_iter().next().unwrap();
+        }
+        Ok(values)
+    },
+);
+
+define_stdfunction!(
+    eval_with {
+        expression: Standard::String,
+        variables: Standard::Object
+    },
+    returns = Any,
+
+    docs = {
+        category: "System",
+        description: "Evaluates a string as a Lavendeux expression, with the given variables defined",
+        ext_description: "
+            Works like eval, but each key of the object is first assigned as a variable in the expression's scope.
+            Only those variables are visible to the expression, and any it assigns are discarded afterwards.
+        ",
+        examples: "
+            assert_eq(7, eval_with('a + b', {'a': 3, 'b': 4}))
+            assert_eq('Hello, world', eval_with('greeting + \", \" + name', {'greeting': 'Hello', 'name': 'world'}))
+
+            eval_with('leaked = 1', {})
+            would_err('leaked')
+        ",
+    },
+    handler = (state, _reference) {
+        let expression = required_arg!(state::expression).to_string();
+        let variables = required_arg!(state::variables).as_a::<Object>()?;
+
+        state.scope_into_eval()?;
+        for (name, value) in variables.keys().iter().zip(variables.values().iter()) {
+            state.set_variable(&name.to_string(), (*value).clone());
+        }
+        let res = Lavendeux::eval(&expression, state).and_then(|n| n.evaluate(state));
+        state.scope_out_eval();
 
-        state.scope_out();
+        let mut values = res?;
         if values.len() == 1 {
             values = values.as_a::<Vec<Value>>()?.into_iter().next().unwrap();
         }
@@ -92,6 +217,102 @@ define_stdfunction!(
     },
 );
 
+define_stdfunction!(
+    with_timeout {
+        expression: Standard::String,
+        milliseconds: Standard::Int
+    },
+    returns = Any,
+
+    docs = {
+        category: "System",
+        description: "Evaluates a string as a Lavendeux expression, failing if it takes too long",
+        ext_description: "
+            Works like eval, but raises a timeout error if the expression runs for longer than the given number of milliseconds.
+            The deadline only applies to this expression; the script's own timeout is unchanged, and still applies.
+        ",
+        examples: "
+            assert_eq(5, with_timeout('2 + 3', 1000))
+
+            spin(n) = spin(n + 1)
+            would_err(\"with_timeout('spin(0)', 10)\")
+        ",
+    },
+    handler = (state, _reference) {
+        let expression = required_arg!(state::expression).to_string();
+        let milliseconds = required_arg!(state::milliseconds).as_a::<i64>()?.max(0);
+
+        // An outer deadline that is sooner still applies
+        let deadline = std::time::Instant::now() + std::time::Duration::from_millis(milliseconds as u64);
+        let outer = state.deadline();
+        state.set_deadline(Some(outer.map_or(deadline, |outer| outer.min(deadline))));
+
+        let res = state.scope_into_eval().and_then(|_| {
+            let res = Lavendeux::eval(&expression, state).and_then(|n| n.evaluate(state));
+            state.scope_out_eval();
+            res
+        });
+        state.set_deadline(outer);
+
+        let mut values = res?;
+        if values.len() == 1 {
+            values = values.as_a::<Vec<Value>>()?.into_iter().next().unwrap();
+        }
+        Ok(values)
+    },
+);
+
+define_stdfunction!(
+    retry {
+        name: Standard::String,
+        attempts: Standard::Int,
+        delay_ms: Standard::Int,
+        args: Optional::Array
+    },
+    returns = Any,
+
+    docs = {
+        category: "System",
+        description: "Calls a function by name until it succeeds, up to a number of attempts",
+        ext_description: "
+            Returns the result of the first successful call, waiting the given number of milliseconds between failures.
+            If every attempt fails, the last error is raised.
+            The wait counts towards the script's timeout, and any with_timeout deadline.
+        ",
+        examples: "
+            tries = 0
+            flaky() = {
+                assign_global('tries', global('tries') + 1)
+                if global('tries') < 3 then error('not yet') else global('tries')
+            }
+            assert_eq(3, retry('flaky', 5, 1))
+
+            would_err(\"retry('error', 3, 1, ['failed'])\")
+        ",
+    },
+    handler = (state, _reference) {
+        let name = required_arg!(state::name).to_string();
+        let attempts = required_arg!(state::attempts).as_a::<i64>()?.max(1);
+        let delay_ms = required_arg!(state::delay_ms).as_a::<i64>()?.max(0);
+        let args = optional_arg!(state::args).map(|a| a.as_a::<Vec<Value>>()).transpose()?.unwrap_or_default();
+
+        let delay = std::time::Duration::from_millis(delay_ms as u64);
+        let mut attempt = 1;
+        loop {
+            match state.call_function(&name, args.clone(), None) {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt >= attempts || matches!(e.root_cause().details, ErrorDetails::Timeout) => {
+                    return Err(e)
+                }
+                Err(_) => {
+                    state.sleep(delay)?;
+                    attempt += 1;
+                }
+            }
+        }
+    },
+);
+
 define_stdfunction!(
     include {
         filename: Standard::String
@@ -103,6 +324,8 @@ define_stdfunction!(
         description: "Evaluates a file as a Lavendeux expression and returns the result",
         ext_description: "
             The file will be interpreted as a script and evaluated in it's own scope.
+            Relative paths in an included file are resolved from that file's directory; elsewhere they are relative to the working directory.
+            A file that includes itself, directly or through other files, is an error.
             Returns an empty string in all cases.
         ",
         examples: "
@@ -110,33 +333,237 @@ define_stdfunction!(
         ",
     },
     handler = (state, _reference) {
-        let script = required_arg!(state::filename).to_string();
-        let script = std::fs::read_to_string(script)?;
+        let filename = required_arg!(state::filename).to_string();
+        let path = state.resolve_include_path(&filename);
+        let script = std::fs::read_to_string(&path)?;
 
-        state.scope_into()?;
-        state.lock_scope();
-        let res = Lavendeux::eval(&script, state).map(|n| n.evaluate(state));
-        match res {
-            Ok(r) => {
-                match r {
-                    Ok(v) => v,
-                    Err(e) => {
-                        state.scope_out();
-                        return Err(e);
-                    }
-                }
-            },
-            Err(e) => {
-                state.scope_out();
-                return Err(e);
-            }
-        };
+        state.push_include(&path)?;
+        if let Err(e) = state.scope_into_eval() {
+            state.pop_include();
+            return Err(e);
+        }
+        let res = Lavendeux::eval(&script, state).and_then(|n| n.evaluate(state));
+        state.pop_include();
+        state.scope_out_eval();
 
-        state.scope_out();
+        res?;
         Ok(Value::from(""))
     },
 );
 
+define_stdfunction!(
+    include_optional {
+        filename: Standard::String
+    },
+    returns = Any,
+
+    docs = {
+        category: "System",
+        description: "Evaluates a file as a Lavendeux expression if it exists",
+        ext_description: "
+            Works like include, but does nothing if the file does not exist.
+            Paths are resolved in the same way as include.
+            Returns an empty string in all cases.
+        ",
+        examples: "
+            include_optional('example_scripts/does_not_exist.lav')
+        ",
+    },
+    handler = (state, _reference) {
+        let filename = required_arg!(state::filename);
+        if !state.resolve_include_path(&filename.to_string()).exists() {
+            return Ok(Value::from(""));
+        }
+
+        state.call_function("include", vec![filename], None)
+    },
+);
+
+define_stdfunction!(
+    import {
+        filename: Standard::String,
+        namespace: Standard::String
+    },
+    returns = Array,
+
+    docs = {
+        category: "System",
+        description: "Loads the functions and variables of a script under a namespace",
+        ext_description: "
+            The file is evaluated on its own, without access to the caller's variables or functions.
+            Functions it defines can then be called as namespace.name(...), and can call each other without the prefix.
+            Variables it defines are stored in an object named after the namespace, and can be read by name from within its functions.
+            Changes made to them inside a function are local to that call; the namespace object is not modified.
+            Paths are resolved in the same way as include, and a name that is already in use is an error.
+            Returns the names of the imported functions.
+        ",
+        examples: "
+            #skip
+            import('lib/geometry.lav', 'geometry')
+            geometry.area(2, 3)
+            geometry['unit']
+        ",
+    },
+    handler = (state, _reference) {
+        let filename = required_arg!(state::filename).to_string();
+        let namespace = required_arg!(state::namespace).to_string();
+        if state.global_get_variable(&namespace).is_some() || state.get_variable_as_parent(&namespace).is_some() {
+            return oops!(ImportCollision { name: namespace });
+        }
+
+        let path = state.resolve_include_path(&filename);
+        let script = std::fs::read_to_string(&path)?;
+
+        let mut module = state.for_import()?;
+        module.push_include(&path)?;
+        let builtins = module.all_functions().keys().cloned().collect::<std::collections::HashSet<_>>();
+        Lavendeux::eval(&script, &mut module).and_then(|n| n.evaluate(&mut module))?;
+
+        let mut functions = vec![];
+        for (name, function) in module.all_functions() {
+            if builtins.contains(name) {
+                continue;
+            }
+
+            let function = NamespacedFunction::new(&namespace, function.clone_self());
+            if state.get_function(function.name()).is_some() {
+                return oops!(ImportCollision { name: function.name().to_string() });
+            }
+            functions.push(function);
+        }
+        functions.sort_by(|a, b| a.name().cmp(b.name()));
+
+        let variables = module.all_variables_unscoped()
+            .into_iter()
+            .filter(|(k, _)| !State::INTERNAL_VARIABLES.contains(k))
+            .map(|(k, v)| (Value::from(k), v.clone()))
+            .collect::<Vec<_>>();
+        state.global_assign_variable(&namespace, Value::try_from(variables)?);
+
+        let mut names = vec![];
+        for function in functions {
+            names.push(Value::from(function.name()));
+            state.register_function(function)?;
+        }
+        Ok(Value::from(names))
+    },
+);
+
+define_stdfunction!(
+    render_template {
+        filename: Standard::String,
+        data: Standard::Object
+    },
+    returns = String,
+
+    docs = {
+        category: "System",
+        description: "Reads a template file, and fills in its placeholders from an object",
+        ext_description: "
+            Each {name} in the file is replaced by the value of that key in the object; {{ and }} produce literal braces.
+            A placeholder naming a key that is not in the object is an error, as is a missing file.
+            Paths are resolved in the same way as include.
+        ",
+        examples: "
+            #skip
+            render_template('templates/config.txt', {'host': 'localhost', 'port': 8080})
+        ",
+    },
+    handler = (state, _reference) {
+        let filename = required_arg!(state::filename).to_string();
+        let data = required_arg!(state::data).as_a::<Object>()?;
+
+        let path = state.resolve_include_path(&filename);
+        let template = std::fs::read_to_string(&path)?;
+
+        let mut output = String::with_capacity(template.len());
+        let mut rest = template.as_str();
+        while let Some(i) = rest.find(['{', '}']) {
+            output.push_str(&rest[..i]);
+            rest = &rest[i..];
+
+            if rest.starts_with("{{") || rest.starts_with("}}") {
+                output.push_str(&rest[..1]);
+                rest = &rest[2..];
+                continue;
+            }
+
+            let end = match rest.find('}') {
+                Some(end) if rest.starts_with('{') => end,
+                _ => return oops!(Custom {
+                    msg: format!("Unmatched brace in template {}", path.display())
+                }),
+            };
+
+            let key = rest[1..end].trim();
+            let value = data.get(&Value::from(key)).or_error(ErrorDetails::Custom {
+                msg: format!("Template {} has no value for {{{key}}}", path.display())
+            })?;
+            output.push_str(&value.to_string());
+            rest = &rest[end + 1..];
+        }
+        output.push_str(rest);
+
+        Ok(Value::from(output))
+    },
+);
+
+define_stdfunction!(
+    benchmark {
+        expression: Standard::String,
+        iterations: Standard::Int
+    },
+    returns = Object,
+
+    docs = {
+        category: "System",
+        description: "Times the evaluation of a Lavendeux expression",
+        ext_description: "
+            The string will be evaluated <iterations> times, each in it's own scope.
+            Returns an object containing the 'total', 'mean', 'min' and 'max' durations, in milliseconds.
+        ",
+        examples: "#skip
+            benchmark('2 ** 64', 100)
+            // {'total': 1.53, 'mean': 0.0153, 'min': 0.012, 'max': 0.031}
+        ",
+    },
+    handler = (state, _reference) {
+        let expression = required_arg!(state::expression).to_string();
+        let iterations = required_arg!(state::iterations).as_a::<i64>()?;
+        if iterations < 1 {
+            return oops!(Custom {
+                msg: "Iterations must be greater than 0".to_string()
+            });
+        }
+
+        let mut timings = Vec::with_capacity(iterations as usize);
+        for _ in 0..iterations {
+            state.scope_into_eval()?;
+
+            let start = std::time::Instant::now();
+            let res = Lavendeux::eval(&expression, state).and_then(|n| n.evaluate(state));
+            let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+
+            state.scope_out_eval();
+            res?;
+            timings.push(elapsed);
+        }
+
+        let total = timings.iter().sum::<f64>();
+        let min = timings.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = timings.iter().copied().fold(0.0, f64::max);
+        let mean = total / timings.len() as f64;
+
+        let result = Object::try_from(vec![
+            (Value::from("total"), Value::from(total)),
+            (Value::from("mean"), Value::from(mean)),
+            (Value::from("min"), Value::from(min)),
+            (Value::from("max"), Value::from(max)),
+        ])?;
+        Ok(result.into())
+    },
+);
+
 define_stdfunction!(
     __exec_tests {
     },
@@ -174,10 +601,9 @@ define_stdfunction!(
 
         let mut errors = vec![];
         for test_case in matching_functions.iter() {
-            state.scope_into()?;
-            state.lock_scope();
+            state.scope_into_eval()?;
             let res = state.call_function(test_case, vec![], None);
-            state.scope_out();
+            state.scope_out_eval();
 
             if let Err(e) = res {
                 errors.push((test_case, e));
@@ -206,6 +632,57 @@ define_stdfunction!(
     },
 );
 
+define_stdfunction!(
+    run_tests {
+    },
+    returns = Object,
+
+    docs = {
+        category: "System",
+        description: "Evaluates all functions beginning with __test_, and returns the results as an object",
+        ext_description: "
+            Like __exec_tests, but never throws - each test name is mapped to an object of the form
+            {'passed': bool, 'error': string}, where error is empty if the test passed.
+        ",
+        examples: "
+            __test_will_fail() = assert_eq(1, 2)
+            __test_will_pass() = assert_eq(1, 1)
+            results = run_tests()
+            assert_eq(false, results['__test_will_fail']['passed'])
+            assert_eq(true, results['__test_will_pass']['passed'])
+            assert_eq('', results['__test_will_pass']['error'])
+        ",
+    },
+    handler = (state, _reference) {
+        let matching_functions = state
+            .all_functions()
+            .iter()
+            .filter(|(name, _)| name.starts_with("__test_"))
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+
+        let mut results = Object::default();
+        for test_case in matching_functions.into_iter() {
+            state.scope_into_eval()?;
+            let res = state.call_function(&test_case, vec![], None);
+            state.scope_out_eval();
+
+            let (passed, error) = match res {
+                Ok(_) => (true, String::new()),
+                Err(e) => (false, e.to_string()),
+            };
+
+            let result = Object::try_from(vec![
+                (Value::from("passed"), Value::from(passed)),
+                (Value::from("error"), Value::from(error)),
+            ])?;
+            results.insert(Value::from(test_case), result.into())?;
+        }
+
+        Ok(results.into())
+    },
+);
+
 define_stdfunction!(
     generate_documentation {},
     returns = String,
@@ -275,6 +752,59 @@ define_stdfunction!(
     },
 );
 
+define_stdfunction!(
+    function_info {
+        name: Standard::String
+    },
+    returns = Object,
+    docs = {
+        category: "System",
+        description: "Returns information about a function",
+        ext_description: "
+            Returns an object describing the function with the given name, for stdlib and user-defined functions alike.
+            The object contains the keys 'name', 'category', 'signature', 'arguments', 'returns' and 'readonly'.
+            Each argument is an object with the keys 'name', 'type', 'optional' and 'plural'.
+        ",
+        examples: "
+            info = function_info('replace')
+            assert_eq(info['category'], 'String')
+            assert_eq(info['arguments'][0]['name'], 's')
+
+            scale(x: int, factor) = x * factor
+            assert_eq(function_info('scale')['arguments'][1]['type'], 'any')
+            assert_eq(function_info('scale')['readonly'], false)
+        ",
+    },
+    handler = (state, _reference) {
+        let name = required_arg!(state::name).to_string();
+        let function = state
+            .get_function(&name)
+            .or_error(ErrorDetails::FunctionName { name: name.clone() })?;
+
+        let arguments = function
+            .expected_arguments()
+            .into_iter()
+            .map(|(name, arg)| {
+                Value::try_from(vec![
+                    (Value::from("name"), Value::from(name.to_string())),
+                    (Value::from("type"), Value::from(arg.expected_type.to_string())),
+                    (Value::from("optional"), Value::from(arg.is_optional())),
+                    (Value::from("plural"), Value::from(arg.is_plural())),
+                ])
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Value::try_from(vec![
+            (Value::from("name"), Value::from(function.name().to_string())),
+            (Value::from("category"), Value::from(function.documentation().category().to_string())),
+            (Value::from("signature"), Value::from(function.signature())),
+            (Value::from("arguments"), Value::array(arguments)),
+            (Value::from("returns"), Value::from(function.return_type().to_string())),
+            (Value::from("readonly"), Value::from(function.is_readonly())),
+        ])?)
+    },
+);
+
 /**********************************************
  *
  * Assertions and Errors
@@ -283,7 +813,8 @@ define_stdfunction!(
 
 define_stdfunction!(
     assert {
-        condition: Standard::Any
+        condition: Standard::Any,
+        message: Optional::String
     },
     returns = Any,
 
@@ -293,128 +824,344 @@ define_stdfunction!(
         ext_description: "
             Does a weak-comparison to boolean, so 0, '', [], etc. are all considered false.
             Returns the value otherwise
+            If a message is given, it is included in the error
         ",
         examples: "
             assert(true)
             assert( would_err('assert(false)') )
+            assert_throws('assert(1 > 2, "1 is not greater than 2")', '1 is not greater than 2')
+        ",
+    },
+    handler = (state, _reference) {
+        let cond = required_arg!(state::condition);
+        if cond.is_truthy() {
+            Ok(cond.clone())
+        } else {
+            let msg = match optional_arg!(state::message) {
+                Some(message) => format!("Assertion failed: {message}"),
+                None => "Assertion failed".to_string(),
+            };
+            oops!(Custom { msg })
+        }
+    },
+);
+
+define_stdfunction!(
+    assert_eq {
+        condition: Standard::Any,
+        expected: Standard::Any,
+        message: Optional::String
+    },
+    returns = Any,
+
+    docs = {
+        category: "System",
+        description: "Asserts that 2 values are equal",
+        ext_description: "
+            Raises an error if the condition is not equal to the expected value.
+            Also verifies type, as opposed to the `==` operator, which uses weak typing.
+            use assert(a == b) if you want to compare values without checking their types.
+            If a message is given, it is included in the error
+        ",
+        examples: "
+            assert_eq(true, true)
+            assert_eq( true, would_err('assert_eq(1, true)') )
+            assert_throws('assert_eq(1, 2, "counts differ")', 'counts differ')
+        ",
+    },
+    handler = (state, _reference) {
+        let cond = required_arg!(state::condition);
+        let expected = required_arg!(state::expected);
+
+        if cond == expected {
+            Ok(cond.clone())
+        } else {
+            let message = match optional_arg!(state::message) {
+                Some(message) => format!("Assertion failed: {message}: {:?} != {:?}", cond, expected),
+                None => format!("Assertion failed: {:?} != {:?}", cond, expected),
+            };
+            oops!(Custom { msg: message })
+        }
+    },
+);
+
+define_stdfunction!(
+    assert_type {
+        value: Standard::Any,
+        type_name: Standard::String
+    },
+    returns = Any,
+
+    docs = {
+        category: "System",
+        description: "Asserts that a value is of the given type",
+        ext_description: "
+            Raises an error if the value is not of the given type, and returns the value otherwise.
+            Accepts any type name usable with the `is` operator, including groups like 'numeric' or 'collection'.
+            Useful for validating the arguments of a function.
+        ",
+        examples: "
+            assert_eq( 5, assert_type(5, 'int') )
+            assert_type([1, 2], 'collection')
+            assert( would_err('assert_type(\"5\", \"numeric\")') )
+        ",
+    },
+    handler = (state, _reference) {
+        let value = required_arg!(state::value);
+        let type_name = required_arg!(state::type_name).to_string();
+        let expected_type = ValueType::try_from(type_name.as_str())?;
+
+        if value.is_a(expected_type) {
+            Ok(value)
+        } else {
+            oops!(Custom {
+                msg: format!("Assertion failed: expected a {expected_type} value, found {} {:?}", value.own_type(), value)
+            })
+        }
+    },
+);
+
+define_stdfunction!(
+    would_err {
+        expression: Standard::String
+    },
+    returns = Bool,
+
+    docs = {
+        category: "System",
+        description: "Returns true if the given expression would raise an error",
+        ext_description: "
+            Returns true if expression given by the string would raise an error, false otherwise.
+            This is useful for testing error messages.
+        ",
+        examples: "
+            assert_eq( false, would_err('1 + 1') )
+            assert_eq( true, would_err('1 + asparagus') )
+        ",
+    },
+    handler = (state, _reference) {
+        let expression = required_arg!(state::expression).to_string();
+        let res = crate::Lavendeux::eval(&expression, state).map(|n| n.evaluate(state));
+        match res {
+            Ok(r) if r.is_ok() => Ok(Value::from(false)),
+            _ => Ok(Value::from(true))
+        }
+    },
+);
+
+define_stdfunction!(
+    assert_throws {
+        expression: Standard::String,
+        substring: Standard::String
+    },
+    returns = String,
+
+    docs = {
+        category: "System",
+        description: "Asserts that the given expression raises a specific error",
+        ext_description: "
+            Evaluates the expression given by the string, and raises an error if it succeeds,
+            or if the error it raises does not contain the given substring.
+            Returns the error message otherwise.
+        ",
+        examples: "
+            assert_throws('1 + asparagus', 'asparagus')
+            assert( would_err('assert_throws(\"1 + 1\", \"\")') )
+            assert( would_err('assert_throws(\"1 + asparagus\", \"broccoli\")') )
+        ",
+    },
+    handler = (state, _reference) {
+        let expression = required_arg!(state::expression).to_string();
+        let substring = required_arg!(state::substring).to_string();
+
+        let res = crate::Lavendeux::eval(&expression, state).and_then(|n| n.evaluate(state));
+        match res {
+            Ok(_) => oops!(Custom {
+                msg: format!("Assertion failed: `{expression}` did not raise an error")
+            }),
+            Err(e) => {
+                let message = e.to_string();
+                if message.contains(&substring) {
+                    Ok(Value::string(message))
+                } else {
+                    oops!(Custom {
+                        msg: format!("Assertion failed: expected an error containing {substring:?}, got:\n{message}")
+                    })
+                }
+            }
+        }
+    },
+);
+
+define_stdfunction!(
+    error {
+        msg: Standard::String,
+        code: Optional::String
+    },
+    returns = Any,
+
+    docs = {
+        category: "System",
+        description: "Throws an error with the given message",
+        ext_description: "
+            Throws an exception with a custom message. The error's source will be the line where the error was thrown.
+            If a code is given, it is included in the message, and used as the error's kind when caught with `try`/`catch`.
+        ",
+        examples: "
+            would_err('error(\"This is an error\")')
+            assert_throws('error(\"Out of stock\", \"InventoryError\")', 'InventoryError: Out of stock')
+            assert_eq(try { error('Out of stock', 'InventoryError') } catch err { err['kind'] }, 'InventoryError')
         ",
     },
     handler = (state, _reference) {
-        let cond = required_arg!(state::condition);
-        if cond.is_truthy() {
-            Ok(cond.clone())
-        } else {
-            oops!(Custom {
-                msg: "Assertion failed".to_string()
-            })
+        let message = required_arg!(state::msg).to_string();
+        match optional_arg!(state::code) {
+            Some(code) => oops!(UserDefined {
+                code: code.to_string(),
+                msg: message
+            }),
+            None => oops!(Custom { msg: message }),
         }
     },
 );
 
 define_stdfunction!(
-    assert_eq {
-        condition: Standard::Any,
-        expected: Standard::Any
+    debug {
+        msg: Standard::String
     },
     returns = Any,
 
     docs = {
         category: "System",
-        description: "Asserts that 2 values are equal",
+        description: "Prints a debug message to the console",
         ext_description: "
-            Raises an error if the condition is not equal to the expected value.
-            Also verifies type, as opposed to the `==` operator, which uses weak typing.
-            use assert(a == b) if you want to compare values without checking their types.
+            The message will be both written to the parser's output, and returned as a string.
+            Output goes to stdout unless the host has redirected it; if the parser is not attached to a console, it will not be visible.
         ",
         examples: "
-            assert_eq(true, true)
-            assert_eq( true, would_err('assert_eq(1, true)') )
+            debug(\"This is a debug message\")
         ",
     },
     handler = (state, _reference) {
-        let cond = required_arg!(state::condition);
-        let expected = required_arg!(state::expected);
-
-        if cond == expected {
-            Ok(cond.clone())
-        } else {
-            let message = format!("Assertion failed: {:?} != {:?}", cond, expected);
-            oops!(Custom { msg: message })
-        }
+        let message = required_arg!(state::msg).to_string();
+        state.write_output(&message)?;
+        Ok(Value::string(message))
     },
 );
 
 define_stdfunction!(
-    would_err {
-        expression: Standard::String
+    warn {
+        msg: Standard::String
     },
-    returns = Bool,
+    returns = String,
 
     docs = {
         category: "System",
-        description: "Returns true if the given expression would raise an error",
+        description: "Records a warning, without stopping the script",
         ext_description: "
-            Returns true if expression given by the string would raise an error, false otherwise.
-            This is useful for testing error messages.
+            Unlike `error`, execution continues after the warning is recorded.
+            Warnings are kept, along with the line that raised them, until the host retrieves them.
+            Returns the message.
         ",
         examples: "
-            assert_eq( false, would_err('1 + 1') )
-            assert_eq( true, would_err('1 + asparagus') )
+            assert_eq( warn('Input looks suspicious'), 'Input looks suspicious' )
         ",
     },
     handler = (state, _reference) {
-        let expression = required_arg!(state::expression).to_string();
-        let res = crate::Lavendeux::eval(&expression, state).map(|n| n.evaluate(state));
-        match res {
-            Ok(r) if r.is_ok() => Ok(Value::from(false)),
-            _ => Ok(Value::from(true))
-        }
+        let message = required_arg!(state::msg).to_string();
+        state.warn(message.clone());
+        Ok(Value::string(message))
     },
 );
 
 define_stdfunction!(
-    error {
-        msg: Standard::String
+    tap {
+        value: Standard::Any,
+        function: Standard::String
     },
     returns = Any,
 
     docs = {
         category: "System",
-        description: "Throws an error with the given message",
+        description: "Calls a function on a value for its side effects, and returns the value unchanged",
         ext_description: "
-            Throws an exception with a custom message. The error's source will be the line where the error was thrown.
+            The result of the named function is discarded, but any error it raises is returned.
+            Useful for inserting calls like debug into a chain of functions without changing its result.
         ",
         examples: "
-            would_err('error(\"This is an error\")')
+            assert_eq(tap(3, 'debug'), 3)
+            assert_eq([1, 2, 3].tap('debug').len(), 3)
         ",
     },
     handler = (state, _reference) {
-        let message = required_arg!(state::msg).to_string();
-        oops!(Custom { msg: message })
+        let value = required_arg!(state::value);
+        let function = required_arg!(state::function).to_string();
+        state.call_function(&function, vec![value.clone()], None)?;
+        Ok(value)
     },
 );
 
 define_stdfunction!(
-    debug {
-        msg: Standard::String
+    dump {
+        value: Standard::Any
     },
     returns = Any,
 
     docs = {
         category: "System",
-        description: "Prints a debug message to the console",
+        description: "Prints a detailed view of a value to the console, and returns the value unchanged",
         ext_description: "
-            The message will be both written to stdout, and returned as a string.
-            If the parser is not attached to a console, it will not be visible.
+            Each value is labelled with its type, and the contents of arrays and objects are printed one per line, indented by depth.
+            Like debug, the text is written to the parser's output, which is stdout unless the host has redirected it.
         ",
         examples: "
-            debug(\"This is a debug message\")
+            assert_eq( dump({'a': [1, 2.5], 'b': 'text'}), {'a': [1, 2.5], 'b': 'text'} )
+            assert_eq( dump([]), [] )
         ",
     },
     handler = (state, _reference) {
-        let message = required_arg!(state::msg).to_string();
-        println!("{message}");
-        Ok(Value::string(message))
+        fn render(value: &Value, depth: usize, output: &mut String) -> Result<(), crate::Error> {
+            let indent = "    ".repeat(depth + 1);
+            let closing = "    ".repeat(depth);
+            if value.is_a(ValueType::Object) {
+                let object = value.as_a::<Object>()?;
+                if object.keys().is_empty() {
+                    output.push_str("object {}");
+                    return Ok(());
+                }
+
+                output.push_str("object {\n");
+                for (key, value) in object.keys().iter().zip(object.values().iter()) {
+                    output.push_str(&format!("{indent}{key}: "));
+                    render(value, depth + 1, output)?;
+                    output.push_str(",\n");
+                }
+                output.push_str(&format!("{closing}}}"));
+            } else if value.is_a(ValueType::Array) {
+                let array = value.as_a::<Vec<Value>>()?;
+                if array.is_empty() {
+                    output.push_str("array []");
+                    return Ok(());
+                }
+
+                output.push_str("array [\n");
+                for value in &array {
+                    output.push_str(&indent);
+                    render(value, depth + 1, output)?;
+                    output.push_str(",\n");
+                }
+                output.push_str(&format!("{closing}]"));
+            } else {
+                output.push_str(&format!("{}({})", value.own_type(), value));
+            }
+            Ok(())
+        }
+
+        let value = required_arg!(state::value);
+        let mut output = String::new();
+        render(&value, 0, &mut output)?;
+        state.write_output(&output)?;
+        Ok(value)
     },
 );
 
@@ -588,10 +1335,363 @@ define_stdfunction!(
     },
 );
 
+define_stdfunction!(
+    can_cast {
+        value: Standard::Any,
+        type_name: Standard::String
+    },
+    returns = Bool,
+
+    docs = {
+        category: "System",
+        description: "Returns true if a value can be cast to the given type",
+        ext_description: "
+            Checks whether `value as type_name` would succeed, without raising an error if it would not.
+            An unknown type name is still an error.
+        ",
+        examples: "
+            assert( can_cast(5.0, 'int') )
+            assert( can_cast([1], 'int') )
+            assert( !can_cast(-5, 'u8') )
+            assert( !can_cast([1, 2, 3], 'int') )
+        ",
+    },
+    handler = (state, _reference) {
+        let value = required_arg!(state::value);
+        let type_name = required_arg!(state::type_name).to_string();
+        let target = ValueType::try_from(type_name.as_str())?;
+        Ok(Value::from(value.as_type(target).is_ok()))
+    },
+);
+
+define_stdfunction!(
+    try_cast {
+        value: Standard::Any,
+        type_name: Standard::String
+    },
+    returns = Any,
+
+    docs = {
+        category: "System",
+        description: "Casts a value to the given type, or returns nil if it cannot be cast",
+        ext_description: "
+            Works like `value as type_name`, but returns nil instead of raising an error if the cast fails.
+            An unknown type name is still an error.
+        ",
+        examples: "
+            assert_eq( try_cast(5.0, 'int'), 5 )
+            assert_eq( try_cast(5, 'array'), [5] )
+            assert_eq( try_cast(-5, 'u8'), nil )
+        ",
+    },
+    handler = (state, _reference) {
+        let value = required_arg!(state::value);
+        let type_name = required_arg!(state::type_name).to_string();
+        let target = ValueType::try_from(type_name.as_str())?;
+        Ok(value.as_type(target).unwrap_or(Value::from(false)))
+    },
+);
+
+define_stdfunction!(
+    common_type {
+        a: Standard::Any,
+        b: Standard::Any
+    },
+    returns = String,
+
+    docs = {
+        category: "System",
+        description: "Returns the type two values would both be converted to in an expression",
+        ext_description: "
+            Follows the same type hierarchy as the operators, so common_type(1, 2.5) is the type of 1 + 2.5.
+            See the type system documentation for the full order.
+        ",
+        examples: "
+            assert_eq( common_type(1, 2.5), typeof(2.5) )
+            assert_eq( common_type(1, 'a'), 'string' )
+            assert_eq( common_type([1], {}), 'object' )
+        ",
+    },
+    handler = (state, _reference) {
+        let a = required_arg!(state::a);
+        let b = required_arg!(state::b);
+        let (a, _) = a.resolve(b)?;
+        Ok(Value::string(a.own_type().to_string()))
+    },
+);
+
+define_stdfunction!(
+    promote {
+        input: Standard::Array
+    },
+    returns = Array,
+
+    docs = {
+        category: "System",
+        description: "Converts every element of an array to the common type of all its elements",
+        ext_description: "
+            The common type is found as with common_type, across the whole array.
+            Useful for normalizing a mixed array before numeric work.
+        ",
+        examples: "
+            assert_eq( promote([1, 2.5, true]), [1.0, 2.5, 1.0] )
+            assert_eq( typeof(promote([1, 2.5])[0]), typeof(1.0) )
+            assert_eq( promote([1, 'a']), ['1', 'a'] )
+            assert_eq( promote([]), [] )
+        ",
+    },
+    handler = (state, _reference) {
+        let input = required_arg!(state::input).as_a::<Vec<Value>>()?;
+        let mut common = match input.first() {
+            Some(first) => first.clone(),
+            None => return Ok(Value::from(input)),
+        };
+        for value in input.iter().skip(1) {
+            (common, _) = common.resolve(value.clone())?;
+        }
+
+        let common_type = common.own_type();
+        let output = input.into_iter().map(|v| v.as_type(common_type)).collect::<Result<Vec<_>, _>>()?;
+        Ok(Value::from(output))
+    },
+);
+
+define_stdfunction!(
+    hash {
+        value: Standard::Any
+    },
+    returns = Int,
+
+    docs = {
+        category: "System",
+        description: "Returns a 64-bit hash of its input",
+        ext_description: "
+            Values that are strictly equal always have the same hash, including between runs.
+            Arrays and objects are hashed by their contents, and the order of keys in an object does not matter.
+            This is not a cryptographic hash; use sha256 where security matters.
+        ",
+        examples: "
+            assert_eq( hash([1, 'a']), hash([1, 'a']) )
+            assert_eq( hash({'a': 1, 'b': 2}), hash({'b': 2, 'a': 1}) )
+            assert( hash(1) != hash(1.0) )
+        ",
+    },
+    handler = (state, _reference) {
+        // FNV-1a, so that hashes do not depend on the platform or compiler version
+        fn fnv(hash: u64, bytes: &[u8]) -> u64 {
+            bytes.iter().fold(hash, |hash, b| (hash ^ *b as u64).wrapping_mul(0x100000001b3))
+        }
+
+        fn hash_value(value: &Value) -> Result<u64, crate::Error> {
+            let hash = fnv(0xcbf29ce484222325, value.own_type().to_string().as_bytes());
+            if value.is_a(ValueType::Object) {
+                // Entries are combined with addition, so that key order does not matter
+                let object = value.as_a::<Object>()?;
+                let mut entries = 0u64;
+                for (key, value) in object.keys().iter().zip(object.values().iter()) {
+                    let entry = fnv(hash_value(key)?, &hash_value(value)?.to_le_bytes());
+                    entries = entries.wrapping_add(entry);
+                }
+                Ok(fnv(hash, &entries.to_le_bytes()))
+            } else if value.is_a(ValueType::Array) {
+                value.as_a::<Vec<Value>>()?.iter().try_fold(hash, |hash, v| {
+                    Ok(fnv(hash, &hash_value(v)?.to_le_bytes()))
+                })
+            } else {
+                Ok(fnv(hash, value.to_string().as_bytes()))
+            }
+        }
+
+        let value = required_arg!(state::value);
+        Ok(Value::from(hash_value(&value)? as i64))
+    },
+);
+
+define_stdfunction!(
+    coalesce {
+        values: Plural::Any
+    },
+    returns = Any,
+
+    docs = {
+        category: "System",
+        description: "Returns the first non-empty value among its arguments",
+        ext_description: "
+            Returns the first argument that is not nil, or an empty string, array or object.
+            If every argument is empty, nil is returned.
+            Unlike an operator, all arguments are evaluated before the call, so this does not short-circuit.
+        ",
+        examples: "
+            assert_eq( 5, coalesce(nil, '', 5, 6) )
+            assert_eq( 0, coalesce([], 0) )
+            assert_eq( nil, coalesce({}, nil) )
+        ",
+    },
+    handler = (state, _reference) {
+        let values = required_arg!(state::values).as_a::<Vec<Value>>()?;
+        let value = values.into_iter().find(|v| {
+            if v.is_a(ValueType::Bool) {
+                v.is_truthy()
+            } else {
+                !(v.is_a(ValueType::Collection) && v.len() == 0)
+            }
+        });
+
+        Ok(value.unwrap_or(Value::from(false)))
+    },
+);
+
+define_stdfunction!(
+    eq {
+        left: Standard::Any,
+        right: Standard::Any
+    },
+    returns = Bool,
+
+    docs = {
+        category: "System",
+        description: "Compares two values using weak equality",
+        ext_description: "
+            Equivalent to the `==` operator; values are converted to a common type before being compared.
+            Numeric types are promoted to the wider of the two, so 1 == 1.0, and booleans compare as numbers, so false == 0.
+            Arrays and objects compare their contents strictly.
+            Useful with higher-order functions that expect a function name.
+        ",
+        examples: "
+            assert( eq(1, 1.0) )
+            assert( eq(false, 0) )
+            assert( !eq('a', 'b') )
+        ",
+    },
+    handler = (state, _reference) {
+        let left = required_arg!(state::left);
+        let right = required_arg!(state::right);
+        Ok(left.boolean_op(right, BooleanOperation::EQ)?)
+    },
+);
+
+define_stdfunction!(
+    strict_eq {
+        left: Standard::Any,
+        right: Standard::Any
+    },
+    returns = Bool,
+
+    docs = {
+        category: "System",
+        description: "Compares two values using strict equality",
+        ext_description: "
+            Equivalent to the `===` operator; values are only equal if they are also of the same type.
+            This is the comparison used by assert_eq.
+        ",
+        examples: "
+            assert( strict_eq(1, 1) )
+            assert( !strict_eq(1, 1.0) )
+            assert( !strict_eq(false, 0) )
+        ",
+    },
+    handler = (state, _reference) {
+        let left = required_arg!(state::left);
+        let right = required_arg!(state::right);
+        Ok(left.boolean_op(right, BooleanOperation::StrictEQ)?)
+    },
+);
+
+define_stdfunction!(
+    deep_equal {
+        left: Standard::Any,
+        right: Standard::Any
+    },
+    returns = Bool,
+
+    docs = {
+        category: "System",
+        description: "Compares two values recursively, ignoring differences between numeric types",
+        ext_description: "
+            Arrays must have the same length, and objects the same keys, and their contents are compared recursively.
+            Numbers are compared by value, so 1 and 1.0 are equal; all other values must be strictly equal.
+            Useful for comparing data parsed from JSON, where integers may become floats.
+        ",
+        examples: "
+            assert( deep_equal({'a': [1, 2.0]}, {'a': [1.0, 2]}) )
+            assert( !deep_equal([1, 2], [1, 2, 3]) )
+            assert( !deep_equal(1, '1') )
+        ",
+    },
+    handler = (state, _reference) {
+        fn compare(left: &Value, right: &Value) -> Result<bool, crate::Error> {
+            if left.is_a(ValueType::Object) && right.is_a(ValueType::Object) {
+                let left = left.as_a::<Object>()?;
+                let right = right.as_a::<Object>()?;
+                if left.keys().len() != right.keys().len() {
+                    return Ok(false);
+                }
+                for (key, value) in left.keys().iter().zip(left.values().iter()) {
+                    match right.get(key) {
+                        Some(other) if compare(value, other)? => {}
+                        _ => return Ok(false),
+                    }
+                }
+                Ok(true)
+            } else if left.is_a(ValueType::Array) && right.is_a(ValueType::Array) {
+                let left = left.as_a::<Vec<Value>>()?;
+                let right = right.as_a::<Vec<Value>>()?;
+                if left.len() != right.len() {
+                    return Ok(false);
+                }
+                for (l, r) in left.iter().zip(right.iter()) {
+                    if !compare(l, r)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            } else if left.is_a(ValueType::Numeric) && right.is_a(ValueType::Numeric) {
+                Ok(left.clone().boolean_op(right.clone(), BooleanOperation::EQ)?.is_truthy())
+            } else {
+                Ok(left == right)
+            }
+        }
+
+        let left = required_arg!(state::left);
+        let right = required_arg!(state::right);
+        Ok(Value::from(compare(&left, &right)?))
+    },
+);
+
 #[cfg(test)]
 mod test {
     use crate::lav;
 
+    lav!(test_compose_order r#"
+        add_a(s) = s + 'a'
+        add_b(s) = s + 'b'
+        add_c(s) = s + 'c'
+
+        assert_eq(call_function(compose('add_a', 'add_b', 'add_c'), ['']), 'cba')
+        assert_eq(call_function(pipe_fns('add_a', 'add_b', 'add_c'), ['']), 'abc')
+    "#);
+
+    lav!(test_compose_nested r#"
+        add(a, b) = a + b
+        double(x) = x * 2
+
+        add_ten = partial('add', [10])
+        assert_eq(call_function(compose('double', add_ten), [1]), 22)
+
+        inner = compose('double', 'double')
+        assert_eq(call_function(pipe_fns(inner, add_ten), [1]), 14)
+        assert_eq(call_function(compose('double', pipe_fns('double', add_ten)), [1]), 24)
+    "#);
+
+    lav!(test_partial r#"
+        add(a, b) = a + b
+        add_ten = partial('add', [10])
+        assert_eq(for x in [1, 2, 3] do call_function(add_ten, [x]), [11, 12, 13])
+
+        add_both = partial('add', [1, 2])
+        assert_eq(call_function(add_both, []), 3)
+    "#);
+
     lav!(test_exec_tests_bad(Error) r#"
         __test_will_fail() = assert_eq(1, 2)
         __test_will_pass() = assert_eq(1, 1)
@@ -602,4 +1702,41 @@ mod test {
         __test_will_pass() = assert_eq(1, 1)
         __exec_tests()
     "#);
+
+    lav!(test_run_tests(passed = false, error_is_empty = true) r#"
+        __test_will_fail() = assert_eq(1, 2)
+        __test_will_pass() = assert_eq(1, 1)
+        results = run_tests()
+        passed = results['__test_will_fail']['passed']
+        error_is_empty = results['__test_will_pass']['error'] == ''
+    "#);
+
+    lav!(test_benchmark(total_gte_max = true, min_lte_max = true) r#"
+        result = benchmark('x = 2 ** 8', 5)
+        total_gte_max = result['total'] >= result['max']
+        min_lte_max = result['min'] <= result['max']
+    "#);
+
+    lav!(test_deep_equal r#"
+        a = {'id': 1, 'tags': ['x', 'y'], 'pos': {'x': 1.5, 'y': 2, 'z': [0, 1]}}
+        b = {'pos': {'z': [0.0, 1.0], 'y': 2.0, 'x': 1.5}, 'tags': ['x', 'y'], 'id': 1.0}
+        assert( deep_equal(a, b) )
+        assert( !(a == b) )
+
+        b['pos']['z'][1] = 2
+        assert( !deep_equal(a, b) )
+        assert( !deep_equal({'a': 1}, {'b': 1}) )
+    "#);
+
+    lav!(test_assert_throws r#"
+        assert_throws('error("custom failure")', 'custom failure')
+    "#);
+
+    lav!(test_assert_throws_wrong_error(Error) r#"
+        assert_throws('error("custom failure")', 'something else')
+    "#);
+
+    lav!(test_assert_throws_no_error(Error) r#"
+        assert_throws('1 + 1', '')
+    "#);
 }
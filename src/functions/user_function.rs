@@ -12,7 +12,7 @@ use polyvalue::{Value, ValueType};
 
 use super::{
     documentation::UserFunctionDocumentation,
-    std_function::{FunctionArgument, FunctionArgumentType, ParserFunction},
+    std_function::{FunctionArgument, FunctionArgumentType, ManageArguments, ParserFunction},
     FunctionDocumentation,
 };
 
@@ -69,7 +69,7 @@ impl ParserFunction for UserDefinedFunction<'_> {
             args: self.args.clone(),
             returns: self.returns,
             src: self.src.clone(),
-            body: UserDefinedFunction::compile(&self.src, &mut Default::default()).unwrap(), // This is safe because the function is already checked
+            body: UserDefinedFunction::compile(&self.name, &self.src, &mut Default::default()).unwrap(), // This is safe because the function is already checked
 
             src_line_offset: self.src_line_offset,
 
@@ -84,15 +84,29 @@ impl ParserFunction for UserDefinedFunction<'_> {
     ) -> Result<Value, Error> {
         // Execute the body - this is checked in the constructor
         // so we can unwrap here
-        match self.body.evaluate(state) {
-            Ok(v) => Ok(v.as_type(self.returns)?),
-            Err(e) => {
-                if let ErrorDetails::Return { value } = e.details {
-                    Ok(value.as_type(self.returns)?)
-                } else {
-                    let e = e.offset_linecount(self.src_line_offset);
-                    Err(e)
-                }
+        let mut result = self.body.evaluate(state);
+        loop {
+            match result {
+                Ok(v) => return self.coerce_return(v),
+                Err(e) => match e.details {
+                    ErrorDetails::Return { value } => return self.coerce_return(value),
+
+                    // Self-recursive tail call - start over in a fresh scope with the new arguments
+                    ErrorDetails::TailCall { arguments } => {
+                        state.check_timer()?;
+                        state.scope_out();
+                        state.scope_into()?;
+                        state.lock_scope();
+                        self.expected_arguments()
+                            .map_arguments(&arguments, state, self.signature())?;
+                        result = self.body.evaluate(state);
+                    }
+
+                    _ => {
+                        let e = e.offset_linecount(self.src_line_offset);
+                        return Err(e);
+                    }
+                },
             }
         }
     }
@@ -101,7 +115,7 @@ impl ParserFunction for UserDefinedFunction<'_> {
 impl UserDefinedFunction<'_> {
     /// Create a new user-defined function
     pub fn new(name: &str, src: String, state: &mut State) -> Result<Self, Error> {
-        let body = Self::compile(&src, state)?;
+        let body = Self::compile(name, &src, state)?;
         Ok(UserDefinedFunction {
             name: name.to_string(),
             args: vec![],
@@ -118,8 +132,20 @@ impl UserDefinedFunction<'_> {
         })
     }
 
-    fn compile(src: &str, state: &mut State) -> Result<Node<'static>, Error> {
-        Lavendeux::eval_rule(src, state, Rule::BLOCK).map(|n| n.into_owned())
+    /// Coerce a value returned by the body to the declared return type
+    fn coerce_return(&self, value: Value) -> Result<Value, Error> {
+        value.as_type(self.returns).or_else(|_| {
+            oops!(FunctionReturnType {
+                expected_type: self.returns,
+                signature: self.signature()
+            })
+        })
+    }
+
+    fn compile(name: &str, src: &str, state: &mut State) -> Result<Node<'static>, Error> {
+        let mut body = Lavendeux::eval_rule(src, state, Rule::BLOCK)?.into_owned();
+        body.mark_tail_calls(name);
+        Ok(body)
     }
 
     /// Add a required argument to the function
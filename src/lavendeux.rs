@@ -1,9 +1,10 @@
-use crate::documentation::{DocumentationTemplate, MarkdownFormatter};
+use crate::documentation::{DocumentationTemplate, MarkdownFormatter, OperatorDocumentation};
+use crate::formatter::{Comment, Formatter};
 use crate::functions::ParserFunction;
 use crate::pest::LavendeuxParser;
 use crate::syntax_tree::traits::NodeExt;
-use crate::syntax_tree::Node;
-use crate::{Error, Rule, State, Value};
+use crate::syntax_tree::{Node, OperatorInfo};
+use crate::{Error, NumberFormat, Rule, State, Value};
 use std::num::NonZeroUsize;
 use std::time::Duration;
 
@@ -16,15 +17,65 @@ pub struct ParserOptions {
     pub timeout: Duration,
 
     /// The maximum number of calls to the pest parser
-    /// This is used to prevent stack overflows
+    /// This is used to prevent stack overflows, and to bound the time spent on pathological input
+    /// The number of calls needed grows with the length of the input, and quickly with the depth of nested brackets;
+    /// large scripts, such as the bundled stdlib, can need tens of millions
+    /// Exceeding it raises `ErrorDetails::PestCallLimit`
+    /// A value of 0 disables the limit, which is only safe for trusted input
     pub pest_call_limit: usize,
+
+    /// Raises the pest call limit to this many calls per byte of input, if that is higher than `pest_call_limit`
+    /// Lets large, well-formed scripts parse without tuning the limit by hand, while short pathological input stays bounded;
+    /// the tradeoff is that a long enough malicious input is also given a proportionally larger budget
+    /// Has no effect if `pest_call_limit` is 0. A value of 0 disables scaling
+    pub pest_call_limit_per_byte: usize,
+
+    /// The maximum depth of nested function calls
+    /// Exceeding it raises an error instead of overflowing the stack
+    /// A value of 0 disables the limit
+    pub max_call_depth: usize,
+
+    /// Decimal and thousands separators used by the numeric and currency decorators
+    pub number_format: NumberFormat,
+
+    /// The maximum nesting depth of eval and include
+    /// A value of 0 disables the limit
+    pub max_eval_depth: usize,
+
+    /// The maximum number of values a single parse can return
+    /// Also bounds the length of arrays built by loops and literals, as they are built
+    /// A value of 0 disables the limit
+    pub max_output_size: usize,
+
+    /// Disables functions that access the filesystem or network, for running untrusted scripts
+    /// Affects include, include_optional, import, tail, render_template, resolve, resolve_all, ping, get, download, post, api_get, api_post, llm and chatgpt
+    /// See [State::SANDBOXED_FUNCTIONS]
+    pub sandbox: bool,
 }
 impl Default for ParserOptions {
     fn default() -> Self {
         Self {
             timeout: Duration::from_secs(0),
             pest_call_limit: 0,
+            pest_call_limit_per_byte: 0,
+            max_call_depth: State::DEFAULT_MAX_CALL_DEPTH,
+            number_format: NumberFormat::default(),
+            max_eval_depth: State::DEFAULT_MAX_EVAL_DEPTH,
+            max_output_size: 0,
+            sandbox: false,
+        }
+    }
+}
+
+impl ParserOptions {
+    /// Returns the pest call limit to use for the given input
+    fn pest_call_limit_for(&self, input: &str) -> Option<NonZeroUsize> {
+        if self.pest_call_limit == 0 {
+            return None;
         }
+
+        let scaled = self.pest_call_limit_per_byte.saturating_mul(input.len());
+        NonZeroUsize::new(self.pest_call_limit.max(scaled))
     }
 }
 
@@ -38,7 +89,13 @@ impl Lavendeux {
     /// Create a new Lavendeux instance
     /// The instance will have a new state
     pub fn new(options: ParserOptions) -> Self {
-        Self::with_state(options.clone(), State::with_timeout(options.timeout))
+        let mut state = State::with_timeout(options.timeout);
+        state.set_max_call_depth(options.max_call_depth);
+        state.set_number_format(options.number_format.clone());
+        state.set_max_eval_depth(options.max_eval_depth);
+        state.set_max_output_size(options.max_output_size);
+        state.set_sandboxed(options.sandbox);
+        Self::with_state(options, state)
     }
 
     /// Create a new Lavendeux instance with a given state
@@ -46,6 +103,12 @@ impl Lavendeux {
         Self { state, options }
     }
 
+    /// Create an independent copy of this parser, with the same options and a fork of its state
+    /// Used to evaluate scripts concurrently against a common base - see [State::fork]
+    pub fn fork(&self) -> Self {
+        Self::with_state(self.options.clone(), self.state.fork())
+    }
+
     /// Register a function with the parser
     pub fn register_function(&mut self, function: impl ParserFunction) -> Result<(), Error> {
         self.state.register_function(function)
@@ -82,11 +145,12 @@ impl Lavendeux {
     /// Returns an array of values, one for each line in the input
     pub fn parse(&mut self, input: &str) -> Result<Vec<Value>, Error> {
         self.state.sanitize_scopes();
-        pest::set_call_limit(NonZeroUsize::new(self.options.pest_call_limit));
+        pest::set_call_limit(self.options.pest_call_limit_for(input));
         self.state.start_timer();
 
         let value = Self::eval(input, &mut self.state)?.evaluate(&mut self.state)?;
         let lines = value.as_a::<Vec<Value>>()?;
+        self.state.check_output_size(lines.len())?;
         Ok(lines)
     }
 
@@ -94,7 +158,25 @@ impl Lavendeux {
     /// Returns an array of values, one for each line in the input
     pub fn run(&mut self, filename: &str) -> Result<Vec<Value>, Error> {
         let input = std::fs::read_to_string(filename)?;
-        self.parse(&input)
+
+        // Includes in the file are resolved relative to it
+        self.state.push_include(std::path::Path::new(filename))?;
+        let result = self.parse(&input);
+        self.state.pop_include();
+        result
+    }
+
+    /// Parses the given script and returns it in a canonical format
+    /// Normalizes operator spacing and block indentation, while keeping comments and line breaks
+    /// Formatting already formatted source returns it unchanged
+    pub fn format_source(input: &str) -> Result<String, Error> {
+        Formatter::format(input)
+    }
+
+    /// Parses the given script and returns every comment in it, in order of appearance
+    /// Comments are otherwise discarded by the parser; this is intended for tooling
+    pub fn comments(input: &str) -> Result<Vec<Comment>, Error> {
+        Formatter::comments(input)
     }
 
     /// Generates markdown formatted documentation for the parser
@@ -102,12 +184,55 @@ impl Lavendeux {
     pub fn generate_documentation(&self) -> String {
         DocumentationTemplate::new(MarkdownFormatter).render(&self.state)
     }
+
+    /// Lists every operator in the language, in order of increasing precedence
+    /// Includes the symbol, associativity, and precedence level of each
+    pub fn operators() -> Vec<OperatorInfo> {
+        crate::syntax_tree::all_operators()
+    }
+
+    /// Returns the documentation for each operator and language construct, sorted by name
+    /// This is the same data used to render the operator section of the documentation
+    pub fn operator_documentation() -> Vec<&'static OperatorDocumentation> {
+        crate::documentation::all_operator_documentation()
+    }
 }
 
 // Tests mostly related to the fuzzer
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::error::ErrorDetails;
+
+    /// A scratch directory for tests that read files from disk
+    /// Removed when dropped, so that a failing test does not leave it behind
+    struct TempDir(std::path::PathBuf);
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("lavendeux_{name}_{}", std::process::id()));
+            std::fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        /// Writes a file into the directory, creating any missing parent directories
+        fn write(&self, path: &str, contents: &str) {
+            let path = self.0.join(path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).unwrap();
+            }
+            std::fs::write(path, contents).unwrap();
+        }
+
+        /// Returns the path of a file in the directory, with forward slashes so it can be used in a script
+        fn path(&self, path: &str) -> String {
+            self.0.join(path).to_str().unwrap().replace('\\', "/")
+        }
+    }
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            std::fs::remove_dir_all(&self.0).ok();
+        }
+    }
 
     #[test]
     fn test_slow_brackets() {
@@ -133,6 +258,288 @@ mod test {
                  .unwrap_err();
     }
 
+    #[test]
+    fn test_pest_call_limit() {
+        let mut parser = Lavendeux::new(ParserOptions {
+            pest_call_limit: 10000,
+            ..Default::default()
+        });
+        parser.parse("1 + 1").unwrap();
+
+        let e = parser.parse(&"x = [1, 2, 3]\n".repeat(1000)).unwrap_err();
+        assert!(matches!(e.details, ErrorDetails::PestCallLimit));
+        assert!(e.to_string().contains("pest_call_limit"));
+    }
+
+    #[test]
+    fn test_pest_call_limit_per_byte() {
+        let options = ParserOptions {
+            pest_call_limit: 10000,
+            pest_call_limit_per_byte: 100000,
+            ..Default::default()
+        };
+        assert_eq!(options.pest_call_limit_for("").unwrap().get(), 10000);
+        assert_eq!(options.pest_call_limit_for("1 + 1").unwrap().get(), 500000);
+
+        let mut parser = Lavendeux::new(options);
+        parser.parse(&"x = [1, 2, 3]\n".repeat(1000)).unwrap();
+
+        let disabled = ParserOptions {
+            pest_call_limit_per_byte: 100000,
+            ..Default::default()
+        };
+        assert!(disabled.pest_call_limit_for("1 + 1").is_none());
+    }
+
+    #[test]
+    fn test_max_call_depth() {
+        let mut parser = Lavendeux::new(ParserOptions {
+            max_call_depth: 10,
+            ..Default::default()
+        });
+        parser.parse("f(x) = x == 0 ? 0 : f(x - 1)").unwrap();
+        parser.parse("f(5)").unwrap();
+
+        let e = parser.parse("f(50)").unwrap_err();
+        assert!(matches!(e.root_cause().details, ErrorDetails::CallDepth { limit: 10 }));
+    }
+
+    #[test]
+    fn test_relative_include() {
+        let dir = TempDir::new("include");
+        dir.write("main.lav", "include('lib/inner.lav')\ninclude_optional('missing.lav')\nhelper_value()");
+        dir.write("lib/inner.lav", "include('helper.lav')");
+        dir.write("lib/helper.lav", "helper_value() = 42");
+
+        let mut parser = Lavendeux::new(Default::default());
+        let result = parser.run(&dir.path("main.lav"));
+        assert_eq!(result.unwrap().last(), Some(&Value::from(42i64)));
+    }
+
+    #[test]
+    fn test_include_cycle() {
+        let dir = TempDir::new("cycle");
+        dir.write("a.lav", "include('b.lav')");
+        dir.write("b.lav", "include('a.lav')");
+
+        let mut parser = Lavendeux::new(Default::default());
+        let e = parser.run(&dir.path("a.lav")).unwrap_err();
+        assert!(matches!(e.root_cause().details, ErrorDetails::IncludeCycle { .. }));
+    }
+
+    #[test]
+    fn test_import() {
+        let dir = TempDir::new("import");
+        dir.write("geometry.lav", "unit = 'cm'\nsquare(x) = x * x\narea(w, h) = square(w) * h\nlabel(x) = (x as string) + unit");
+        let path = dir.path("geometry.lav");
+
+        let mut parser = Lavendeux::new(Default::default());
+        let result = parser.parse(&format!("import('{path}', 'geo')"));
+        let area = parser.parse("geo.area(2, 3)");
+        let unit = parser.parse("geo['unit']");
+        let namespace = parser.parse("geo");
+        let global = parser.parse("square(2)");
+        let again = parser.parse(&format!("import('{path}', 'geo')"));
+
+        // The module's own helper is used, even if the caller defines one with the same name
+        parser.parse("square(x) = x + 100").unwrap();
+        let shadowed = parser.parse("geo.area(2, 3)");
+
+        // Module functions read the module's own variables, not the caller's
+        parser.parse("unit = 'in'").unwrap();
+        let label = parser.parse("geo.label(5)");
+
+        assert_eq!(
+            result.unwrap()[0],
+            Value::from(vec![Value::from("geo.area"), Value::from("geo.label"), Value::from("geo.square")])
+        );
+        assert_eq!(area.unwrap()[0], Value::from(12i64));
+        assert_eq!(unit.unwrap()[0], Value::from("cm"));
+        assert_eq!(
+            namespace.unwrap()[0],
+            Value::try_from(vec![(Value::from("unit"), Value::from("cm"))]).unwrap()
+        );
+        global.unwrap_err();
+        again.unwrap_err();
+        assert_eq!(shadowed.unwrap()[0], Value::from(12i64));
+        assert_eq!(label.unwrap()[0], Value::from("5cm"));
+    }
+
+    #[test]
+    fn test_import_timeout() {
+        let dir = TempDir::new("import_timeout");
+        dir.write("slow.lav", "values = for 0..1000000000 do 1");
+        let path = dir.path("slow.lav");
+
+        // The deadline set by with_timeout also applies while the module runs
+        let mut parser = Lavendeux::new(Default::default());
+        let e = parser.parse(&format!("with_timeout(\"import('{path}', 'slow')\", 50)")).unwrap_err();
+        assert!(matches!(e.root_cause().details, ErrorDetails::Timeout));
+    }
+
+    #[test]
+    fn test_render_template() {
+        let dir = TempDir::new("template");
+        dir.write("config.txt", "host={host}:{ port } {{literal}}");
+        let path = dir.path("config.txt");
+
+        let mut parser = Lavendeux::new(Default::default());
+        let result = parser.parse(&format!("render_template('{path}', {{'host': 'localhost', 'port': 8080}})"));
+        let missing_key = parser.parse(&format!("render_template('{path}', {{'host': 'localhost'}})"));
+
+        assert_eq!(result.unwrap()[0], Value::from("host=localhost:8080 {literal}"));
+        missing_key.unwrap_err();
+        parser.parse("render_template('does_not_exist.txt', {})").unwrap_err();
+    }
+
+    #[test]
+    fn test_eval_limits() {
+        let mut parser = Lavendeux::new(ParserOptions {
+            max_eval_depth: 3,
+            max_output_size: 2,
+            ..Default::default()
+        });
+        parser.parse("f(n) = n == 0 ? 0 : eval('f(' + ((n - 1) as string) + ')')").unwrap();
+        parser.parse("f(2)").unwrap();
+
+        let e = parser.parse("f(10)").unwrap_err();
+        assert!(matches!(e.root_cause().details, ErrorDetails::EvalDepth { limit: 3 }));
+
+        // Scripts run by benchmark count towards the same limit
+        parser.parse("g(n) = n == 0 ? 0 : benchmark('g(' + ((n - 1) as string) + ')', 1)").unwrap();
+        let e = parser.parse("g(10)").unwrap_err();
+        assert!(matches!(e.root_cause().details, ErrorDetails::EvalDepth { limit: 3 }));
+
+        parser.parse("1; 2").unwrap();
+        let e = parser.parse("1; 2; 3").unwrap_err();
+        assert!(matches!(e.details, ErrorDetails::OutputSize { limit: 2 }));
+
+        // Arrays are bounded while they are built, not once evaluation finishes
+        let e = parser.parse("for 0..100000000 do 1").unwrap_err();
+        assert!(matches!(e.root_cause().details, ErrorDetails::OutputSize { limit: 2 }));
+        let e = parser.parse("[1, 2, 3]").unwrap_err();
+        assert!(matches!(e.root_cause().details, ErrorDetails::OutputSize { limit: 2 }));
+    }
+
+    #[test]
+    fn test_try_timeout() {
+        let mut parser = Lavendeux::new(ParserOptions {
+            timeout: Duration::from_millis(200),
+            ..Default::default()
+        });
+        parser.parse("spin() = for 0..1000000000 do 1").unwrap();
+
+        // Timeouts raised inside a function call are not caught
+        let e = parser.parse("try { spin() } catch 0").unwrap_err();
+        assert!(matches!(e.root_cause().details, ErrorDetails::Timeout));
+    }
+
+    #[test]
+    fn test_retry() {
+        let mut parser = Lavendeux::new(ParserOptions {
+            timeout: Duration::from_millis(200),
+            ..Default::default()
+        });
+        parser
+            .parse("tries = 0; flaky(n) = { assign_global('tries', global('tries') + 1); global('tries') < n ? error('failed') : global('tries') }")
+            .unwrap();
+
+        // Fails twice, then succeeds on the third attempt
+        assert_eq!(
+            parser.parse("retry('flaky', 3, 1, [3])").unwrap(),
+            vec![Value::from(3i64)]
+        );
+
+        // Runs out of attempts, and raises the last error
+        let e = parser.parse("tries = 0; retry('flaky', 2, 1, [3])").unwrap_err();
+        assert!(matches!(e.root_cause().details, ErrorDetails::Custom { .. }));
+        assert_eq!(parser.parse("tries").unwrap(), vec![Value::from(2i64)]);
+
+        // The delay between attempts is bounded by the timeout
+        let e = parser.parse("tries = 0; retry('flaky', 3, 10000, [3])").unwrap_err();
+        assert!(matches!(e.root_cause().details, ErrorDetails::Timeout));
+    }
+
+    #[test]
+    fn test_sandbox() {
+        let mut parser = Lavendeux::new(ParserOptions {
+            sandbox: true,
+            ..Default::default()
+        });
+
+        for script in ["include('example_scripts/stdlib.lav')", "tail('.gitignore')"] {
+            let e = parser.parse(script).unwrap_err();
+            assert!(matches!(e.root_cause().details, ErrorDetails::Sandboxed { .. }));
+        }
+
+        parser.parse("call_function('include', ['example_scripts/stdlib.lav'])").unwrap_err();
+        parser.parse("eval('1 + 1')").unwrap();
+    }
+
+    #[test]
+    fn test_fork() {
+        let mut base = Lavendeux::new(ParserOptions::default());
+        base.parse("rate = 3; scale(x, r) = x * r").unwrap();
+
+        let handles = (0..4)
+            .map(|i| {
+                let mut parser = base.fork();
+                std::thread::spawn(move || {
+                    parser.parse(&format!("rate = rate + {i}; scale(2, rate)")).unwrap()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for (i, handle) in handles.into_iter().enumerate() {
+            let result = handle.join().unwrap();
+            assert_eq!(result[1], Value::from(2 * (3 + i as i64)));
+        }
+
+        // Changes made by the forks are not seen by the base
+        assert_eq!(base.parse("scale(2, rate)").unwrap(), vec![Value::from(6i64)]);
+    }
+
+    #[test]
+    fn test_number_format() {
+        let mut parser = Lavendeux::new(ParserOptions {
+            number_format: NumberFormat {
+                decimal_separator: ',',
+                thousands_separator: Some('.'),
+                grouping: 3,
+            },
+            ..Default::default()
+        });
+        assert_eq!(parser.parse("1234567 @int").unwrap()[0], Value::from("1.234.567"));
+        assert_eq!(parser.parse("1234.5 @usd").unwrap()[0], Value::from("$1.234,50"));
+
+        let mut parser = Lavendeux::new(Default::default());
+        assert_eq!(parser.parse("1234567 @int").unwrap()[0], Value::from("1234567"));
+    }
+
+    #[test]
+    fn test_operators() {
+        let operators = Lavendeux::operators();
+        let find = |rule| operators.iter().find(|op| op.rule == rule).unwrap();
+
+        let add = find(Rule::OP_ADD);
+        let mul = find(Rule::OP_MUL);
+        let pow = find(Rule::OP_POW);
+        assert_eq!(add.symbol, "+");
+        assert!(add.precedence < mul.precedence);
+        assert!(!add.right_associative);
+        assert!(pow.right_associative);
+        assert_eq!(find(Rule::PREFIX_NEG).operator_type, crate::PrattOperatorType::Prefix);
+
+        assert!(!Lavendeux::operator_documentation().is_empty());
+    }
+
+    #[test]
+    fn test_format_source() {
+        let formatted = Lavendeux::format_source("x=[1,2]\nf(a)={a*2}").unwrap();
+        assert_eq!(formatted, "x = [1, 2]\nf(a) = { a * 2 }");
+        Lavendeux::format_source("x = (1").unwrap_err();
+    }
+
     #[test]
     fn test_large_fixed_convert() {
         let mut parser = Lavendeux::new(Default::default());
@@ -13,6 +13,7 @@ mod network;
 // Docgen utilities
 #[macro_use]
 mod documentation;
+pub use documentation::OperatorDocumentation;
 
 // Errors and error-adjacent gubbins
 #[macro_use]
@@ -23,7 +24,11 @@ pub use error::Error;
 pub mod pest;
 pub use pest::Rule; // exported for Token
 mod syntax_tree;
-pub use syntax_tree::AssignmentTarget;
+pub use syntax_tree::{AssignmentTarget, OperatorInfo, PrattOperatorType};
+
+// Canonical source formatting and comment extraction
+mod formatter;
+pub use formatter::Comment;
 
 /// Function related definitions
 /// Home of the stdlib, user-functions, and function docs
@@ -33,6 +38,13 @@ pub mod functions;
 mod state;
 pub use state::State;
 
+// Locale settings for rendering numbers
+mod number_format;
+pub use number_format::NumberFormat;
+
+// Lossless value encoding, for hosts that store results
+pub mod serialization;
+
 // A token parsed from the input
 // Comes up in error handling
 mod token;
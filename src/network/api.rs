@@ -1,7 +1,7 @@
-use super::request;
+use super::{merge_headers, request, DEFAULT_TIMEOUT};
 use crate::{error::ErrorDetails, Error};
 use polyvalue::{types::Object, Value, ValueTrait, ValueType};
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
 
 #[derive(Debug, Clone, Default)]
 pub struct ApiDefinition {
@@ -10,6 +10,9 @@ pub struct ApiDefinition {
     pub description: String,
     pub examples: String,
     pub auth_key: Option<String>,
+
+    /// Time to wait for a response, if different from the default
+    pub timeout: Option<Duration>,
 }
 
 impl ApiDefinition {
@@ -17,15 +20,17 @@ impl ApiDefinition {
         &self,
         endpoint: Option<&str>,
         body: Option<String>,
-        mut headers: HashMap<String, String>,
+        headers: HashMap<String, String>,
     ) -> Result<Value, Error> {
         let endpoint = endpoint.unwrap_or_default().trim_start_matches('/');
         let target = format!("{}/{}", &self.base_url, endpoint);
+
+        let mut headers = merge_headers(self.additional_headers.clone(), headers);
         if let Some(auth_key) = &self.auth_key {
             headers.insert("Authorization".to_string(), format!("Bearer {}", auth_key));
         }
 
-        request(&target, body, headers)
+        request(&target, body, headers, self.timeout.unwrap_or(DEFAULT_TIMEOUT))
     }
 }
 
@@ -45,7 +50,7 @@ impl TryFrom<Value> for ApiDefinition {
         value
             .get(&Value::from("base_url"))
             .ok_or(ErrorDetails::ValueFormat {
-                expected_format: "<base_url: string> | {<base_url: string>, <description: string>, <examples: string>, <auth_key: string>, <headers: object>}".to_string(),
+                expected_format: "<base_url: string> | {<base_url: string>, <description: string>, <examples: string>, <auth_key: string>, <additional_headers: object>, <timeout: int>}".to_string(),
             })?.to_string();
 
         base_url = base_url.trim_end_matches('/').to_string();
@@ -64,6 +69,12 @@ impl TryFrom<Value> for ApiDefinition {
 
             auth_key: value.get(&("auth_key".into())).map(|v| v.to_string()),
 
+            timeout: value
+                .get(&("timeout".into()))
+                .map(|v| v.clone().as_a::<i64>())
+                .transpose()?
+                .map(|ms| Duration::from_millis(ms.max(0) as u64)),
+
             additional_headers: value
                 .get(&("additional_headers".into()))
                 .unwrap_or(&Value::from(Object::new(Default::default())))
@@ -90,6 +101,11 @@ impl From<ApiDefinition> for Value {
             obj.insert("auth_key".into(), Value::from(auth_key)).ok();
         }
 
+        if let Some(timeout) = val.timeout {
+            obj.insert("timeout".into(), Value::from(timeout.as_millis() as i64))
+                .ok();
+        }
+
         obj.insert(
             "additional_headers".into(),
             Value::try_from(
@@ -104,3 +120,38 @@ impl From<ApiDefinition> for Value {
         Value::from(obj)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let api = ApiDefinition {
+            base_url: "https://example.com".to_string(),
+            additional_headers: HashMap::from([("Accept".to_string(), "text/plain".to_string())]),
+            timeout: Some(Duration::from_secs(5)),
+            ..Default::default()
+        };
+
+        let api = ApiDefinition::try_from(Value::from(api)).unwrap();
+        assert_eq!(api.base_url, "https://example.com");
+        assert_eq!(api.additional_headers["Accept"], "text/plain");
+        assert_eq!(api.timeout, Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_without_optional_fields() {
+        // Definitions stored before timeouts were added have no timeout key
+        let value = Value::try_from(vec![(
+            Value::from("base_url"),
+            Value::from("https://example.com/"),
+        )])
+        .unwrap();
+
+        let api = ApiDefinition::try_from(value).unwrap();
+        assert_eq!(api.base_url, "https://example.com");
+        assert!(api.additional_headers.is_empty());
+        assert_eq!(api.timeout, None);
+    }
+}
@@ -1,5 +1,5 @@
 use super::ApiDefinition;
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
 
 pub fn default_apis() -> HashMap<String, ApiDefinition> {
     let mut apis = HashMap::new();
@@ -53,6 +53,7 @@ pub fn default_apis() -> HashMap<String, ApiDefinition> {
             additional_headers: vec![("Content-Type".to_string(), "application/json".to_string())]
                 .into_iter()
                 .collect(),
+            timeout: Some(Duration::from_secs(30)),
             ..Default::default()
         },
     );
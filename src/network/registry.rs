@@ -5,7 +5,7 @@ use std::collections::HashMap;
 
 pub struct ApiRegistry(HashMap<String, ApiDefinition>);
 impl ApiRegistry {
-    const STORE_NAME: &'static str = "__api_definitions";
+    pub(crate) const STORE_NAME: &'static str = "__api_definitions";
 
     /// Create a new instance of the registry, loading the APIs
     /// from the state object
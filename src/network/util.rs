@@ -1,26 +1,124 @@
-use polyvalue::Value;
+use polyvalue::{types::Object, Value, ValueType};
 use std::collections::HashMap;
-use std::net::ToSocketAddrs;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs};
+use std::path::Path;
 use std::str::FromStr;
-use std::time::Duration;
-
-use crate::{oops, Error};
-
-pub fn resolve(hostname: &str) -> Result<Value, Error> {
-    match (hostname, 0).to_socket_addrs() {
-        Ok(mut addresses) => {
-            if let Some(addr) = addresses.next() {
-                Ok(Value::from(addr.ip().to_string()))
-            } else {
-                oops!(Custom {
-                    msg: format!("No addresses found for `{hostname}`")
-                })
-            }
+use std::time::{Duration, Instant};
+
+use crate::{oops, Error, State};
+
+/// Timeout used for requests that do not specify their own
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// Name of the global variable in which the default request headers are stored
+/// Like the API registry, they are kept in the root scope, so they persist between parses and are copied by `State::fork`
+pub(crate) const DEFAULT_HEADERS_STORE_NAME: &str = "__default_headers";
+
+/// Returns the headers that are sent with every `get` and `post` request
+pub fn default_headers(state: &State) -> HashMap<String, String> {
+    state
+        .global_get_variable(DEFAULT_HEADERS_STORE_NAME)
+        .and_then(|v| v.clone().as_a::<Object>().ok())
+        .unwrap_or_default()
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+/// Replaces the headers that are sent with every `get` and `post` request
+pub fn set_default_headers(state: &mut State, headers: Object) {
+    state.global_assign_variable(DEFAULT_HEADERS_STORE_NAME, Value::from(headers));
+}
+
+/// Merges the given per-call headers over the default headers
+/// Header names are case-insensitive, so a per-call header replaces any default of the same name
+pub fn merge_headers(
+    defaults: HashMap<String, String>,
+    headers: HashMap<String, String>,
+) -> HashMap<String, String> {
+    let mut merged = defaults
+        .into_iter()
+        .filter(|(k, _)| !headers.keys().any(|h| h.eq_ignore_ascii_case(k)))
+        .collect::<HashMap<_, _>>();
+    merged.extend(headers);
+    merged
+}
+
+/// Resolves a hostname to all of its IP addresses, in the order given by the system's resolver
+pub fn resolve_all(hostname: &str) -> Result<Vec<IpAddr>, Error> {
+    unique_addresses(hostname, (hostname, 0).to_socket_addrs()?)
+}
+
+/// Removes duplicate addresses returned by a resolver
+/// Resolvers can return an empty list for some hostnames, which is reported as an error
+fn unique_addresses(
+    hostname: &str,
+    resolved: impl Iterator<Item = SocketAddr>,
+) -> Result<Vec<IpAddr>, Error> {
+    let mut addresses: Vec<IpAddr> = vec![];
+    for addr in resolved {
+        if !addresses.contains(&addr.ip()) {
+            addresses.push(addr.ip());
         }
-        Err(e) => Err(e.into()),
+    }
+
+    if addresses.is_empty() {
+        oops!(Custom {
+            msg: format!("No addresses found for `{hostname}`")
+        })
+    } else {
+        Ok(addresses)
+    }
+}
+
+/// Resolves a hostname to a single IP address
+/// IPv4 addresses are preferred, unless `ipv6` is set, in which case only IPv6 addresses are returned
+pub fn resolve(hostname: &str, ipv6: bool) -> Result<Value, Error> {
+    let addresses = resolve_all(hostname)?;
+    let address = if ipv6 {
+        addresses.iter().find(|addr| addr.is_ipv6())
+    } else {
+        addresses
+            .iter()
+            .find(|addr| addr.is_ipv4())
+            .or(addresses.first())
+    };
+
+    match address {
+        Some(addr) => Ok(Value::from(addr.to_string())),
+        None => oops!(Custom {
+            msg: format!("No IPv6 addresses found for `{hostname}`")
+        }),
     }
 }
 
+/// Checks whether a host accepts TCP connections on the given port
+/// Returns the time taken to connect, or `None` if the host could not be reached before the timeout
+pub fn ping(hostname: &str, port: u16, timeout: Duration) -> Option<Duration> {
+    let start = Instant::now();
+
+    // Lookups can block for far longer than the timeout, so they are left to finish on their own thread
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let host = hostname.to_string();
+    std::thread::spawn(move || {
+        let addresses = (host.as_str(), port)
+            .to_socket_addrs()
+            .map(|a| a.collect::<Vec<_>>());
+        sender.send(addresses).ok();
+    });
+    let addresses = receiver.recv_timeout(timeout).ok()?.ok()?;
+
+    for addr in addresses {
+        let remaining = timeout.checked_sub(start.elapsed()).filter(|t| !t.is_zero())?;
+        if TcpStream::connect_timeout(&addr, remaining).is_ok() {
+            return Some(start.elapsed());
+        }
+    }
+    None
+}
+
 fn decode_response(response: &str, headers: &HashMap<String, String>) -> Value {
     let json_decode = headers.get("Content-Type").cloned().unwrap_or_default()
         == "application/json"
@@ -36,19 +134,41 @@ fn decode_response(response: &str, headers: &HashMap<String, String>) -> Value {
     Value::from(response)
 }
 
+/// Walks a dot-separated path, such as `choices.0.message.content`, into a decoded response
+/// Numeric keys index into arrays; returns `None` if any part of the path is missing
+pub fn value_at_path(value: Value, path: &str) -> Option<Value> {
+    let mut value = value;
+    for key in path.split('.').filter(|k| !k.is_empty()) {
+        value = match value.own_type() {
+            ValueType::Array => {
+                let array = value.as_a::<Vec<Value>>().ok()?;
+                array.get(key.parse::<usize>().ok()?).cloned()?
+            }
+            ValueType::Object => {
+                let object = value.as_a::<Object>().ok()?;
+                object.get(&Value::from(key)).cloned()?
+            }
+            _ => return None,
+        };
+    }
+    Some(value)
+}
+
 /// Fetch from a given URL
 ///
 /// # Arguments
 /// * `url` - Target URL
 /// * `body` - Body if POST
 /// * `headers` - Array of header=value strings
+/// * `timeout` - Time to wait for a response
 pub fn request(
     url: &str,
     body: Option<String>,
     headers: HashMap<String, String>,
+    timeout: Duration,
 ) -> Result<Value, Error> {
     match reqwest::blocking::Client::builder()
-        .timeout(Duration::from_millis(1500))
+        .timeout(timeout)
         .build()
     {
         Ok(client) => {
@@ -75,3 +195,128 @@ pub fn request(
         Err(e) => Err(e.into()),
     }
 }
+
+/// Streams the body of a GET request to a file, without holding it in memory
+/// `check` is called between chunks, and can abort the download by returning an error
+/// Returns the number of bytes written
+pub fn download(
+    url: &str,
+    path: &Path,
+    headers: HashMap<String, String>,
+    check: impl Fn() -> Result<(), Error>,
+) -> Result<u64, Error> {
+    // The whole transfer is not bounded by a fixed timeout, since large files can take a while
+    let client = reqwest::blocking::Client::builder()
+        .connect_timeout(DEFAULT_TIMEOUT)
+        .timeout(None)
+        .build()?;
+
+    let mut request = client.get(url);
+    for (header, value) in headers.iter() {
+        request = request.header(header, value);
+    }
+    let mut response = request.send()?.error_for_status()?;
+
+    let mut file = match File::create(path) {
+        Ok(file) => file,
+        Err(e) => {
+            return oops!(Custom {
+                msg: format!("Could not create `{}`: {e}", path.display())
+            })
+        }
+    };
+
+    let mut buffer = [0u8; 8192];
+    let mut written = 0;
+    loop {
+        check()?;
+        let n = match response.read(&mut buffer) {
+            Ok(0) => return Ok(written),
+            Ok(n) => n,
+            Err(e) => {
+                return oops!(Custom {
+                    msg: format!("Download from `{url}` failed: {e}")
+                })
+            }
+        };
+
+        if let Err(e) = file.write_all(&buffer[..n]) {
+            return oops!(Custom {
+                msg: format!("Could not write to `{}`: {e}", path.display())
+            });
+        }
+        written += n as u64;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_unique_addresses() {
+        let v4 = SocketAddr::from(([127, 0, 0, 1], 0));
+        let v6 = SocketAddr::from(([0, 0, 0, 0, 0, 0, 0, 1], 0));
+        let addresses = unique_addresses("localhost", vec![v4, v6, v4].into_iter()).unwrap();
+        assert_eq!(addresses, vec![v4.ip(), v6.ip()]);
+
+        let e = unique_addresses("localhost", std::iter::empty()).unwrap_err();
+        assert_eq!(e.to_string(), "No addresses found for `localhost`");
+    }
+
+    #[test]
+    fn test_value_at_path() {
+        let response = r#"{
+            "id": "chatcmpl-123",
+            "object": "chat.completion",
+            "choices": [{
+                "index": 0,
+                "message": { "role": "assistant", "content": "Hello there!" },
+                "finish_reason": "stop"
+            }]
+        }"#;
+        let response = Value::try_from(serde_json::Value::from_str(response).unwrap()).unwrap();
+
+        assert_eq!(
+            value_at_path(response.clone(), "choices.0.message.content"),
+            Some(Value::from("Hello there!"))
+        );
+        assert_eq!(
+            value_at_path(response.clone(), "object"),
+            Some(Value::from("chat.completion"))
+        );
+        assert_eq!(value_at_path(response.clone(), "choices.1.message"), None);
+        assert_eq!(value_at_path(response.clone(), "choices.first"), None);
+        assert_eq!(value_at_path(response, "id.0"), None);
+    }
+
+    #[test]
+    fn test_ping() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert!(ping("127.0.0.1", port, Duration::from_millis(500)).is_some());
+
+        drop(listener);
+        assert!(ping("127.0.0.1", port, Duration::from_millis(500)).is_none());
+        assert!(ping("127.0.0.1", port, Duration::ZERO).is_none());
+
+        // Unresolvable hosts still respect the timeout
+        let start = Instant::now();
+        assert!(ping("does-not-exist.invalid", port, Duration::from_millis(200)).is_none());
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_merge_headers() {
+        let defaults = HashMap::from([
+            ("User-Agent".to_string(), "lavendeux".to_string()),
+            ("Accept".to_string(), "text/plain".to_string()),
+        ]);
+        let headers = HashMap::from([("accept".to_string(), "application/json".to_string())]);
+
+        let merged = merge_headers(defaults, headers);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged["User-Agent"], "lavendeux");
+        assert_eq!(merged["accept"], "application/json");
+    }
+}
@@ -0,0 +1,102 @@
+/// Locale settings used by the numeric and currency decorators when rendering numbers
+/// The default leaves output unchanged, using '.' as the decimal separator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumberFormat {
+    /// Character placed between the integer and fractional parts of a number
+    pub decimal_separator: char,
+
+    /// Character placed between groups of digits in the integer part of a number
+    /// Grouping is disabled if this is `None`
+    pub thousands_separator: Option<char>,
+
+    /// Number of digits in each group
+    pub grouping: usize,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            decimal_separator: '.',
+            thousands_separator: None,
+            grouping: 3,
+        }
+    }
+}
+
+impl NumberFormat {
+    /// Reformats the first number found in the given text using these settings
+    /// The text is expected to use '.' as a decimal separator, and ',' between groups of digits
+    pub fn apply(&self, text: &str) -> String {
+        if *self == Self::default() {
+            return text.to_string();
+        }
+
+        let start = match text.find(|c: char| c.is_ascii_digit()) {
+            Some(start) => start,
+            None => return text.to_string(),
+        };
+        let end = text[start..]
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+            .map_or(text.len(), |i| start + i);
+
+        let (integer, fraction) = match text[start..end].split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (&text[start..end], None),
+        };
+        let integer = integer.replace(',', "");
+
+        let mut number = match self.thousands_separator {
+            Some(separator) => group_digits(&integer, separator.encode_utf8(&mut [0; 4]), self.grouping),
+            None => integer,
+        };
+        if let Some(fraction) = fraction {
+            number.push(self.decimal_separator);
+            number.push_str(fraction);
+        }
+
+        format!("{}{}{}", &text[..start], number, &text[end..])
+    }
+}
+
+/// Inserts a separator between each group of `size` digits, counting from the right
+pub(crate) fn group_digits(digits: &str, separator: &str, size: usize) -> String {
+    if size == 0 {
+        return digits.to_string();
+    }
+
+    let mut output = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % size == 0 {
+            output.push_str(separator);
+        }
+        output.push(c);
+    }
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let format = NumberFormat {
+            decimal_separator: ',',
+            thousands_separator: Some('.'),
+            grouping: 3,
+        };
+        assert_eq!(format.apply("$1,234,567.50"), "$1.234.567,50");
+        assert_eq!(format.apply("-1234.5%"), "-1.234,5%");
+        assert_eq!(format.apply("100"), "100");
+        assert_eq!(NumberFormat::default().apply("1,234.5"), "1,234.5");
+    }
+
+    #[test]
+    fn test_group_digits() {
+        assert_eq!(group_digits("1234567", ",", 3), "1,234,567");
+        assert_eq!(group_digits("123", ",", 3), "123");
+        assert_eq!(group_digits("123456", " ", 2), "12 34 56");
+        assert_eq!(group_digits("1234567", "\u{202F}", 3), "1\u{202F}234\u{202F}567");
+        assert_eq!(group_digits("1234", "<>", 3), "1<>234");
+    }
+}
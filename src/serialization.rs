@@ -0,0 +1,134 @@
+//! Lossless encoding of values, for hosts that store computed results and restore them later
+//! Unlike `to_json`, the exact type of every value is kept - including integer widths, fixed-point precision and currency symbols
+//!
+//! Values are written as tagged JSON, in the form `{"type": "i64", "value": "5"}`
+use crate::{error::ErrorDetails, Error};
+use polyvalue::{types::*, Value, ValueTrait, ValueType};
+use serde_json::json;
+use std::str::FromStr;
+
+const FORMAT: &str = "{\"type\": <type name>, \"value\": <value>}";
+
+/// Encodes a value into bytes, which can be restored with [from_bytes]
+pub fn to_bytes(value: &Value) -> Result<Vec<u8>, Error> {
+    Ok(serde_json::to_vec(&encode(value)?)?)
+}
+
+/// Restores a value encoded with [to_bytes]
+pub fn from_bytes(bytes: &[u8]) -> Result<Value, Error> {
+    decode(&serde_json::from_slice(bytes)?)
+}
+
+fn encode(value: &Value) -> Result<serde_json::Value, Error> {
+    let type_name = value.own_type().to_string();
+    let inner = match value.own_type() {
+        ValueType::Array => value
+            .as_a::<Vec<Value>>()?
+            .iter()
+            .map(encode)
+            .collect::<Result<Vec<_>, _>>()?
+            .into(),
+
+        // Keys can be of any type, so objects are stored as a list of pairs
+        ValueType::Object => {
+            let object = value.as_a::<Object>()?;
+            object
+                .keys()
+                .into_iter()
+                .zip(object.values())
+                .map(|(k, v)| Ok(json!([encode(k)?, encode(v)?])))
+                .collect::<Result<Vec<_>, Error>>()?
+                .into()
+        }
+
+        ValueType::Range => {
+            let range = value.as_a::<Range>()?.into_inner();
+            json!([range.start(), range.end()])
+        }
+
+        _ => value.to_string().into(),
+    };
+
+    Ok(json!({ "type": type_name, "value": inner }))
+}
+
+fn decode(encoded: &serde_json::Value) -> Result<Value, Error> {
+    let invalid = || ErrorDetails::ValueFormat {
+        expected_format: FORMAT.to_string(),
+    };
+
+    let type_name = encoded["type"].as_str().ok_or_else(invalid)?;
+    let inner = &encoded["value"];
+    let text = inner.as_str().unwrap_or_default();
+
+    Ok(match type_name {
+        "array" => {
+            let values = inner.as_array().ok_or_else(invalid)?;
+            Value::from(values.iter().map(decode).collect::<Result<Vec<_>, _>>()?)
+        }
+
+        "object" => {
+            let pairs = inner.as_array().ok_or_else(invalid)?;
+            let pairs = pairs
+                .iter()
+                .map(|pair| match pair.as_array().map(|p| p.as_slice()) {
+                    Some([k, v]) => Ok((decode(k)?, decode(v)?)),
+                    _ => Err(Error::from(invalid())),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Value::try_from(pairs)?
+        }
+
+        "range" => match (inner[0].as_i64(), inner[1].as_i64()) {
+            (Some(start), Some(end)) => Value::range(start..=end),
+            _ => return Err(invalid().into()),
+        },
+
+        "bool" => Value::from(text == "true"),
+        "string" => Value::from(text),
+        "float" => Float::from_str(text)?.into(),
+        "fixed" => Value::fixed(Fixed::from_str(text)?),
+        "currency" => Value::currency(Currency::from_str(text)?),
+
+        "u8" => U8::from_str(text)?.into(),
+        "i8" => I8::from_str(text)?.into(),
+        "u16" => U16::from_str(text)?.into(),
+        "i16" => I16::from_str(text)?.into(),
+        "u32" => U32::from_str(text)?.into(),
+        "i32" => I32::from_str(text)?.into(),
+        "u64" => U64::from_str(text)?.into(),
+        "i64" => I64::from_str(text)?.into(),
+
+        _ => return Err(invalid().into()),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Lavendeux;
+
+    #[test]
+    fn test_round_trip() {
+        let mut parser = Lavendeux::new(Default::default());
+        let literals = [
+            "true", "5", "5u8", "-5i8", "5u16", "-5i16", "5u32", "-5i32", "5u64", "1.25", "1.50D",
+            "$1.00", "2.5€", "'text'", "1..5", "[1, 'a', [2.0]]", "{'a': 1, 2: ['b']}", "[]",
+        ];
+
+        for literal in literals {
+            let value = parser.parse(literal).unwrap().remove(0);
+            let restored = from_bytes(&to_bytes(&value).unwrap()).unwrap();
+            assert_eq!(restored, value, "{literal} did not round-trip");
+            assert_eq!(restored.own_type(), value.own_type());
+            assert_eq!(restored.to_string(), value.to_string());
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        from_bytes(b"{\"type\": \"i64\", \"value\": \"five\"}").unwrap_err();
+        from_bytes(b"{\"type\": \"unknown\", \"value\": 1}").unwrap_err();
+        from_bytes(b"[1, 2]").unwrap_err();
+    }
+}
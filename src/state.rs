@@ -1,18 +1,35 @@
 use crate::{
     documentation::{DocumentationFormatter, PlaintextFormatter},
-    error::ErrorDetails,
+    error::{ErrorDetails, Warning},
     functions::{stdlib, ParserFunction},
-    network::ApiRegistry,
+    network::{ApiRegistry, DEFAULT_HEADERS_STORE_NAME},
     syntax_tree::AssignmentTarget,
-    Error, Value,
+    Error, NumberFormat, Token, Value,
 };
 use std::{
     collections::HashMap,
+    io::Write,
+    path::{Path, PathBuf},
     time::{Duration, Instant},
 };
 
+#[cfg(feature = "crypto-functions")]
+use rand::{rngs::StdRng, SeedableRng};
+
+/// Destination for text printed by scripts
+struct OutputSink(Box<dyn Write + Send>);
+impl std::fmt::Debug for OutputSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("OutputSink")
+    }
+}
+
 /// The main parser state
 /// Stores variables, scoping data, functions, and metadata about the current parse
+///
+/// A state can be moved between threads, but not shared by them
+/// To evaluate scripts concurrently, keep a base state holding the shared functions and variables,
+/// and give each thread its own copy of it with [State::fork]
 #[derive(Debug)]
 pub struct State {
     /// Current depth of the parser
@@ -21,18 +38,62 @@ pub struct State {
     depth: usize,
     locked: Vec<usize>,
 
+    /// Current depth of nested function calls
+    /// Bounded by `max_call_depth` to prevent runaway recursion
+    call_depth: usize,
+    max_call_depth: usize,
+
+    /// Current nesting depth of eval and include
+    /// Bounded by `max_eval_depth` to prevent runaway nested evaluation
+    eval_depth: usize,
+    max_eval_depth: usize,
+
+    /// Limit on the number of values in the output, and in any array built by a loop or literal
+    max_output_size: usize,
+
+    /// If true, functions that access the filesystem or network are disabled
+    sandboxed: bool,
+
+    /// Locale settings used by decorators when rendering numbers
+    number_format: NumberFormat,
+
+    /// Where text printed by scripts is written
+    /// Defaults to stdout if unset
+    output: Option<OutputSink>,
+
+    /// Non-fatal diagnostics raised by scripts, until taken by the host
+    warnings: Vec<Warning>,
+
     /// The time that the current parse started
     /// This is used to prevent infinite loops
     /// and implement a timeout
     parse_starttime: Instant,
     timeout: Duration,
 
+    /// An additional deadline, tighter than the timeout, for the expression being evaluated
+    deadline: Option<Instant>,
+
     /// Registered variables
     /// Used as a stack for scoping
     variables: Vec<HashMap<String, Value>>,
 
     /// Registered functions
     functions: HashMap<String, Box<dyn ParserFunction>>,
+
+    /// Files currently being included, innermost last
+    /// Relative include paths are resolved from the directory of the innermost file
+    includes: Vec<PathBuf>,
+
+    /// Namespaces of the imported functions currently being called, innermost last
+    namespaces: Vec<String>,
+
+    /// Constants that are bound as variables in the code being compiled, such as `e` in `catch e { ... }`
+    shadowed_constants: Vec<String>,
+
+    /// Random number generator used by the stdlib
+    /// Created from entropy on first use, unless seeded
+    #[cfg(feature = "crypto-functions")]
+    rng: Option<StdRng>,
 }
 
 impl Default for State {
@@ -41,11 +102,27 @@ impl Default for State {
         let mut instance = Self {
             depth: 0,
             locked: Vec::new(),
+            call_depth: 0,
+            max_call_depth: Self::DEFAULT_MAX_CALL_DEPTH,
+            eval_depth: 0,
+            max_eval_depth: Self::DEFAULT_MAX_EVAL_DEPTH,
+            max_output_size: 0,
+            sandboxed: false,
+            number_format: NumberFormat::default(),
+            output: None,
+            warnings: Vec::new(),
             parse_starttime: std::time::Instant::now(),
             timeout: Duration::from_secs(0),
+            deadline: None,
             variables: vec![HashMap::new()],
 
             functions: stdlib_fns,
+            includes: Vec::new(),
+            namespaces: Vec::new(),
+            shadowed_constants: Vec::new(),
+
+            #[cfg(feature = "crypto-functions")]
+            rng: None,
         };
 
         ApiRegistry::populate_defaults(&mut instance);
@@ -57,6 +134,38 @@ impl Default for State {
 impl State {
     const MAX_DEPTH: usize = 15000;
 
+    /// Global variables used as internal stores, which survive a reset and are never exported
+    pub(crate) const INTERNAL_VARIABLES: [&'static str; 2] =
+        [ApiRegistry::STORE_NAME, DEFAULT_HEADERS_STORE_NAME];
+
+    /// Default limit on the depth of nested function calls
+    pub const DEFAULT_MAX_CALL_DEPTH: usize = 2500;
+
+    /// Default limit on the nesting depth of eval and include
+    pub const DEFAULT_MAX_EVAL_DEPTH: usize = 64;
+
+    /// Functions that access the filesystem or network, and are disabled in sandbox mode
+    pub const SANDBOXED_FUNCTIONS: &'static [&'static str] = &[
+        // Filesystem
+        "include",
+        "include_optional",
+        "import",
+        "tail",
+        "render_template",
+        // Filesystem and network
+        "download",
+        // Network
+        "resolve",
+        "resolve_all",
+        "ping",
+        "get",
+        "post",
+        "api_get",
+        "api_post",
+        "llm",
+        "chatgpt",
+    ];
+
     /// Creates a new parser state
     pub fn new() -> Self {
         Self::default()
@@ -85,15 +194,41 @@ impl State {
         self.parse_starttime = Instant::now();
     }
 
-    /// Checks the timeout of the parser
+    /// Checks the timeout of the parser, and the current deadline if one is set
     pub fn check_timer(&self) -> Result<(), Error> {
         if !self.timeout.is_zero() && self.parse_starttime.elapsed() > self.timeout {
             Err(ErrorDetails::Timeout.into())
+        } else if self.deadline.is_some_and(|deadline| Instant::now() > deadline) {
+            Err(ErrorDetails::Timeout.into())
         } else {
             Ok(())
         }
     }
 
+    /// Waits for the given duration, failing early if the timeout or deadline is reached
+    pub fn sleep(&self, duration: Duration) -> Result<(), Error> {
+        let end = Instant::now() + duration;
+        loop {
+            self.check_timer()?;
+            let now = Instant::now();
+            if now >= end {
+                return Ok(());
+            }
+            std::thread::sleep((end - now).min(Duration::from_millis(10)));
+        }
+    }
+
+    /// Returns the current deadline, if one is set
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Sets a deadline after which `check_timer` fails, in addition to the timeout
+    /// Returns the previous deadline, so that it can be restored
+    pub fn set_deadline(&mut self, deadline: Option<Instant>) -> Option<Instant> {
+        std::mem::replace(&mut self.deadline, deadline)
+    }
+
     /**
      *
      * Scope handling functions
@@ -108,10 +243,33 @@ impl State {
     /// Sets the depth to 0, and destroys all scopes but the root scope
     pub fn sanitize_scopes(&mut self) {
         self.depth = 0;
+        self.call_depth = 0;
+        self.eval_depth = 0;
+        self.deadline = None;
         self.locked = Vec::new();
         self.variables.truncate(1);
     }
 
+    /// Clears all variables and user-defined functions, so that the state can be reused for an independent evaluation
+    /// The standard library, registered APIs and default request headers are kept
+    pub fn reset(&mut self) {
+        self.sanitize_scopes();
+
+        let kept = Self::INTERNAL_VARIABLES
+            .into_iter()
+            .filter_map(|name| Some((name, self.global_delete_variable(name)?)))
+            .collect::<Vec<_>>();
+        self.variables[0].clear();
+        for (name, value) in kept {
+            self.global_assign_variable(name, value);
+        }
+
+        self.functions.retain(|_, function| function.is_readonly());
+        self.includes.clear();
+        self.namespaces.clear();
+        self.warnings.clear();
+    }
+
     /// Creates a new scope from this state
     /// A limit is placed on the depth of scopes that can be created
     /// This is to prevent infinite recursion
@@ -157,6 +315,27 @@ impl State {
         }
     }
 
+    /// Creates a new locked scope for evaluating a nested script, as with eval or include
+    /// Fails if the nesting limit has been reached
+    pub fn scope_into_eval(&mut self) -> Result<(), Error> {
+        if self.max_eval_depth != 0 && self.eval_depth >= self.max_eval_depth {
+            return oops!(EvalDepth {
+                limit: self.max_eval_depth
+            });
+        }
+
+        self.scope_into()?;
+        self.lock_scope();
+        self.eval_depth += 1;
+        Ok(())
+    }
+
+    /// Removes a scope created by `scope_into_eval`
+    pub fn scope_out_eval(&mut self) {
+        self.eval_depth = self.eval_depth.saturating_sub(1);
+        self.scope_out();
+    }
+
     /// Removes the current scope from this state
     pub fn scope_out(&mut self) {
         if self.depth == 0 {
@@ -408,11 +587,236 @@ impl State {
         args: Vec<Value>,
         reference: Option<&AssignmentTarget>,
     ) -> Result<Value, Error> {
-        let function = self.get_function(name).ok_or(ErrorDetails::FunctionName {
+        if self.sandboxed && Self::SANDBOXED_FUNCTIONS.contains(&name) {
+            return oops!(Sandboxed {
+                name: name.to_string()
+            });
+        }
+
+        // Imported functions can call others from their namespace without the prefix
+        // Names in the active namespace take priority, so callers cannot shadow a module's own functions
+        let namespaced = self
+            .namespaces
+            .last()
+            .and_then(|namespace| self.get_function(&format!("{namespace}.{name}")));
+        let function = namespaced.or_else(|| self.get_function(name));
+        let function = function.ok_or(ErrorDetails::FunctionName {
             name: name.to_string(),
         })?;
         let function = function.clone_self();
-        function.exec(&args, self, reference)
+
+        if self.max_call_depth != 0 && self.call_depth >= self.max_call_depth {
+            return oops!(CallDepth {
+                limit: self.max_call_depth
+            });
+        }
+
+        self.call_depth += 1;
+        let result = function.exec(&args, self, reference);
+        self.call_depth -= 1;
+        result
+    }
+
+    /// Sets the maximum depth of nested function calls
+    /// A value of 0 disables the limit
+    pub fn set_max_call_depth(&mut self, max_call_depth: usize) {
+        self.max_call_depth = max_call_depth;
+    }
+
+    /// Returns the maximum depth of nested function calls
+    pub fn max_call_depth(&self) -> usize {
+        self.max_call_depth
+    }
+
+    /// Enables or disables sandbox mode
+    /// While sandboxed, the functions in [State::SANDBOXED_FUNCTIONS] raise an error instead of running
+    pub fn set_sandboxed(&mut self, sandboxed: bool) {
+        self.sandboxed = sandboxed;
+    }
+
+    /// Returns true if sandbox mode is enabled
+    pub fn is_sandboxed(&self) -> bool {
+        self.sandboxed
+    }
+
+    /// Sets the maximum nesting depth of eval and include
+    /// A value of 0 disables the limit
+    pub fn set_max_eval_depth(&mut self, max_eval_depth: usize) {
+        self.max_eval_depth = max_eval_depth;
+    }
+
+    /// Returns the maximum nesting depth of eval and include
+    pub fn max_eval_depth(&self) -> usize {
+        self.max_eval_depth
+    }
+
+    /// Sets the maximum number of values in the output, and in any array built by a loop or literal
+    /// A value of 0 disables the limit
+    pub fn set_max_output_size(&mut self, max_output_size: usize) {
+        self.max_output_size = max_output_size;
+    }
+
+    /// Returns the maximum number of values in the output, and in any array built by a loop or literal
+    pub fn max_output_size(&self) -> usize {
+        self.max_output_size
+    }
+
+    /// Returns an error if a collection of the given length would exceed the output size limit
+    pub(crate) fn check_output_size(&self, len: usize) -> Result<(), Error> {
+        if self.max_output_size != 0 && len > self.max_output_size {
+            oops!(OutputSize {
+                limit: self.max_output_size
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Sets the locale settings used by decorators when rendering numbers
+    pub fn set_number_format(&mut self, number_format: NumberFormat) {
+        self.number_format = number_format;
+    }
+
+    /// Returns the locale settings used by decorators when rendering numbers
+    pub fn number_format(&self) -> &NumberFormat {
+        &self.number_format
+    }
+
+    /// Resolves the path of a file to include
+    /// Relative paths are taken from the directory of the file currently being included, if any
+    pub fn resolve_include_path(&self, filename: &str) -> PathBuf {
+        let path = Path::new(filename);
+        match self.includes.last().and_then(|file| file.parent()) {
+            Some(directory) if path.is_relative() => directory.join(path),
+            _ => path.to_path_buf(),
+        }
+    }
+
+    /// Marks a file as being included, until `pop_include` is called
+    /// Fails if the file is already being included, which would otherwise loop forever
+    pub fn push_include(&mut self, path: &Path) -> Result<(), Error> {
+        let path = path.canonicalize()?;
+        if self.includes.contains(&path) {
+            return oops!(IncludeCycle {
+                path: path.display().to_string()
+            });
+        }
+
+        self.includes.push(path);
+        Ok(())
+    }
+
+    /// Marks the innermost file being included as finished
+    pub fn pop_include(&mut self) {
+        self.includes.pop();
+    }
+
+    /// Creates an empty state for evaluating an imported script
+    /// Keeps the limits and include stack of this state, but none of its variables or functions
+    /// Counts towards the nesting limit of eval and include
+    pub(crate) fn for_import(&self) -> Result<Self, Error> {
+        if self.max_eval_depth != 0 && self.eval_depth >= self.max_eval_depth {
+            return oops!(EvalDepth {
+                limit: self.max_eval_depth
+            });
+        }
+
+        Ok(Self {
+            max_call_depth: self.max_call_depth,
+            eval_depth: self.eval_depth + 1,
+            max_eval_depth: self.max_eval_depth,
+            max_output_size: self.max_output_size,
+            sandboxed: self.sandboxed,
+            parse_starttime: self.parse_starttime,
+            timeout: self.timeout,
+            deadline: self.deadline,
+            includes: self.includes.clone(),
+            ..Self::default()
+        })
+    }
+
+    /// Creates an independent copy of this state, for evaluating scripts on another thread
+    /// The copy starts with the functions, global variables and settings of this state, but with its own scopes, timer and output
+    /// Changes made to the copy are not visible to this state, or to other copies
+    pub fn fork(&self) -> Self {
+        Self {
+            max_call_depth: self.max_call_depth,
+            max_eval_depth: self.max_eval_depth,
+            max_output_size: self.max_output_size,
+            sandboxed: self.sandboxed,
+            number_format: self.number_format.clone(),
+            timeout: self.timeout,
+            variables: vec![self.variables[0].clone()],
+            functions: self
+                .functions
+                .iter()
+                .map(|(name, function)| (name.clone(), function.clone_self()))
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Marks a namespace as active, until `pop_namespace` is called
+    pub(crate) fn push_namespace(&mut self, namespace: &str) {
+        self.namespaces.push(namespace.to_string());
+    }
+
+    /// Marks the innermost active namespace as finished
+    pub(crate) fn pop_namespace(&mut self) {
+        self.namespaces.pop();
+    }
+
+    /// Treats the named constant as a variable in code compiled until `unshadow_constant` is called
+    pub(crate) fn shadow_constant(&mut self, name: &str) {
+        self.shadowed_constants.push(name.to_string());
+    }
+
+    /// Ends the innermost constant shadowing
+    pub(crate) fn unshadow_constant(&mut self) {
+        self.shadowed_constants.pop();
+    }
+
+    /// Returns true if the named constant is currently treated as a variable
+    pub(crate) fn is_shadowed_constant(&self, name: &str) -> bool {
+        self.shadowed_constants.iter().any(|c| c == name)
+    }
+
+    /// Sets where text printed by scripts, such as by `debug`, is written
+    /// Allows a host without a console to capture and display it
+    pub fn set_output(&mut self, output: Box<dyn Write + Send>) {
+        self.output = Some(OutputSink(output));
+    }
+
+    /// Writes a line of text printed by a script to the output sink, or stdout if none is set
+    pub fn write_output(&mut self, text: &str) -> Result<(), Error> {
+        match &mut self.output {
+            Some(OutputSink(output)) => {
+                writeln!(output, "{text}")?;
+                output.flush()?;
+            }
+            None => println!("{text}"),
+        }
+        Ok(())
+    }
+
+    /// Records a non-fatal diagnostic, without interrupting execution
+    pub fn warn(&mut self, message: impl Into<String>) {
+        self.warnings.push(Warning::new(message));
+    }
+
+    /// Returns all warnings raised since the last call, and clears them
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Attaches the location of a function call to the warnings it raised
+    pub(crate) fn locate_warnings(&mut self, token: &Token) {
+        for warning in self.warnings.iter_mut().rev() {
+            if warning.context.is_some() {
+                break;
+            }
+            warning.context = Some(token.clone().into_owned());
+        }
     }
 
     /// Calls a decorator function
@@ -429,6 +833,20 @@ impl State {
         }
     }
 
+    /// Seeds the random number generator used by the stdlib
+    /// The seed persists for the lifetime of the state, making random functions repeatable
+    #[cfg(feature = "crypto-functions")]
+    pub fn seed_random(&mut self, seed: u64) {
+        self.rng = Some(StdRng::seed_from_u64(seed));
+    }
+
+    /// Returns the random number generator used by the stdlib
+    /// If it has not been seeded, it is created from entropy on first use
+    #[cfg(feature = "crypto-functions")]
+    pub fn rng(&mut self) -> &mut StdRng {
+        self.rng.get_or_insert_with(StdRng::from_entropy)
+    }
+
     /// Returns a string containing the help for all functions
     pub fn help(&self, filter: Option<String>) -> String {
         PlaintextFormatter.format_functions(self, filter.as_deref())
@@ -487,4 +905,73 @@ mod test {
         assert!(variables.contains_key("a"));
         assert!(variables.contains_key("b"));
     }
+
+    #[test]
+    fn test_help_user_functions() {
+        let mut parser = crate::Lavendeux::new(Default::default());
+        parser.parse("greet(name: string, greeting) = greeting + name").unwrap();
+        parser
+            .parse("document_function('greet', {'description': 'Greets someone'})")
+            .unwrap();
+
+        let help = parser.state().help(Some("greet".to_string()));
+        assert!(help.contains("greet(name:string, greeting) -> any"));
+        assert!(help.contains("Greets someone"));
+
+        let help = parser.state().help(None);
+        assert!(help.contains("User-Defined Functions"));
+        assert!(help.contains("greet(name:string, greeting) -> any"));
+
+        let help = parser.state().help(Some("user-defined functions".to_string()));
+        assert!(help.contains("greet(name:string, greeting) -> any"));
+    }
+
+    #[test]
+    fn test_output() {
+        #[derive(Clone, Default)]
+        struct Buffer(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+        impl Write for Buffer {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let buffer = Buffer::default();
+        let mut parser = crate::Lavendeux::new(Default::default());
+        parser.state_mut().set_output(Box::new(buffer.clone()));
+        parser.parse("debug('hello'); dump(1)").unwrap();
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(output, "hello\ni64(1)\n");
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut parser = crate::Lavendeux::new(Default::default());
+        parser.parse("x = 5; f(a) = a * 2").unwrap();
+
+        let state = parser.state_mut();
+        state.reset();
+        assert!(state.get_variable("x").is_none());
+        assert!(state.get_function("f").is_none());
+        assert!(state.get_function("len").is_some());
+        assert!(state.global_get_variable(ApiRegistry::STORE_NAME).is_some());
+    }
+
+    #[test]
+    fn test_warnings() {
+        let mut parser = crate::Lavendeux::new(Default::default());
+        parser.parse("x = 1\nwarn('careful'); x + 1").unwrap();
+
+        let warnings = parser.state_mut().take_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].message, "careful");
+        assert_eq!(warnings[0].context.as_ref().map(|t| t.line), Some(2));
+        assert!(parser.state_mut().take_warnings().is_empty());
+    }
 }
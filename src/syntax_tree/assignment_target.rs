@@ -9,6 +9,7 @@ use super::{
 };
 use polyvalue::{
     operations::{IndexingMutationExt, IndexingOperationExt},
+    types::Object,
     Value, ValueType,
 };
 
@@ -23,6 +24,12 @@ pub enum AssignmentTarget<'i> {
 
     /// Destructure a value into multiple targets ( [a, b, c] )
     Destructure(Vec<AssignmentTarget<'i>>),
+
+    /// Collects the remaining values of a destructuring assignment into an array ( [a, ...b] )
+    Rest(Box<AssignmentTarget<'i>>),
+
+    /// Destructure an object into multiple targets by key ( {a, b: c, d?} )
+    DestructureObject(Vec<(String, AssignmentTarget<'i>, bool)>), // (key, target, optional)
 }
 
 impl std::fmt::Display for AssignmentTarget<'_> {
@@ -55,6 +62,24 @@ impl std::fmt::Display for AssignmentTarget<'_> {
                         .join(",")
                 )
             }
+            Self::Rest(target) => write!(f, "...{}", target),
+            Self::DestructureObject(entries) => {
+                write!(
+                    f,
+                    "{{{}}}",
+                    entries
+                        .iter()
+                        .map(|(key, target, optional)| {
+                            let optional = if *optional { "?" } else { "" };
+                            match target {
+                                Self::Identifier(id) if id == key => format!("{key}{optional}"),
+                                _ => format!("{key}{optional}: {target}"),
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                        .join(",")
+                )
+            }
         }
     }
 }
@@ -74,11 +99,57 @@ impl IntoOwned for AssignmentTarget<'_> {
             Self::Destructure(targets) => {
                 Self::Owned::Destructure(targets.into_iter().map(|t| t.into_owned()).collect())
             }
+            Self::Rest(target) => Self::Owned::Rest(Box::new(target.into_owned())),
+            Self::DestructureObject(entries) => Self::Owned::DestructureObject(
+                entries
+                    .into_iter()
+                    .map(|(k, t, o)| (k, t.into_owned(), o))
+                    .collect(),
+            ),
         }
     }
 }
 
 impl<'i> AssignmentTarget<'i> {
+    /// Returns true if this target is a rest element ( ...a )
+    pub fn is_rest(&self) -> bool {
+        matches!(self, Self::Rest(_))
+    }
+
+    /// Split a value between the targets of a destructuring assignment
+    /// A rest element, if present, receives an array of any values not claimed by the other targets
+    pub(crate) fn destructure<'t>(
+        targets: &'t [AssignmentTarget<'i>],
+        value: Value,
+    ) -> Result<Vec<(&'t AssignmentTarget<'i>, Value)>, Error> {
+        let mut values = value.as_a::<Vec<Value>>()?;
+        match targets.iter().position(|t| t.is_rest()) {
+            None if targets.len() != values.len() => oops!(DestructuringAssignment {
+                expected_length: targets.len(),
+                actual_length: values.len()
+            }),
+            None => Ok(targets.iter().zip(values).collect()),
+
+            Some(rest_idx) => {
+                let required = targets.len() - 1;
+                if values.len() < required {
+                    return oops!(DestructuringAssignment {
+                        expected_length: required,
+                        actual_length: values.len()
+                    });
+                }
+
+                let tail = values.split_off(rest_idx + values.len() - required);
+                let rest = values.split_off(rest_idx);
+                let values = values
+                    .into_iter()
+                    .chain(std::iter::once(Value::from(rest)))
+                    .chain(tail);
+                Ok(targets.iter().zip(values).collect())
+            }
+        }
+    }
+
     pub(crate) fn get_index_handle(base: Value, indices: &[Option<Value>]) -> Result<Value, Error> {
         let mut base = base;
         for index in indices {
@@ -107,6 +178,25 @@ impl<'i> AssignmentTarget<'i> {
         Ok(base)
     }
 
+    /// Split an object between the targets of an object destructuring assignment
+    /// Missing keys are an error, unless the entry is optional, in which case nil is used
+    pub(crate) fn destructure_object<'t>(
+        entries: &'t [(String, AssignmentTarget<'i>, bool)],
+        value: Value,
+    ) -> Result<Vec<(&'t AssignmentTarget<'i>, Value)>, Error> {
+        let object = value.as_a::<Object>()?;
+        entries
+            .iter()
+            .map(|(key, target, optional)| {
+                match object.get(&Value::from(key.as_str())) {
+                    Some(value) => Ok((target, value.clone())),
+                    None if *optional => Ok((target, Value::from(false))),
+                    None => oops!(DestructuringKey { key: key.clone() }),
+                }
+            })
+            .collect()
+    }
+
     /// Evaluate the target to get the value it points to
     pub fn get_value(&self, state: &mut State) -> Result<Value, Error> {
         match self {
@@ -126,11 +216,26 @@ impl<'i> AssignmentTarget<'i> {
                     .or_error(ErrorDetails::VariableName { name: base.clone() })?;
                 Self::get_index_handle(base, &idx)
             }
-            Self::Destructure(targets) => targets
-                .iter()
-                .map(|t| t.get_value(state))
-                .collect::<Result<Vec<_>, _>>()
-                .map(Value::from),
+            Self::Destructure(targets) => {
+                let mut values = vec![];
+                for target in targets {
+                    let value = target.get_value(state)?;
+                    if target.is_rest() {
+                        values.extend(value.as_a::<Vec<Value>>()?);
+                    } else {
+                        values.push(value);
+                    }
+                }
+                Ok(Value::from(values))
+            }
+            Self::Rest(target) => target.get_value(state),
+            Self::DestructureObject(entries) => {
+                let mut values = vec![];
+                for (key, target, _) in entries {
+                    values.push((Value::from(key.as_str()), target.get_value(state)?));
+                }
+                Ok(Value::try_from(values)?)
+            }
         }
     }
 
@@ -154,11 +259,26 @@ impl<'i> AssignmentTarget<'i> {
                     .or_error(ErrorDetails::VariableName { name: base.clone() })?;
                 Self::get_index_handle(base, &idx)
             }
-            Self::Destructure(targets) => targets
-                .iter()
-                .map(|t| t.get_value_in_parent(state))
-                .collect::<Result<Vec<_>, _>>()
-                .map(Value::from),
+            Self::Destructure(targets) => {
+                let mut values = vec![];
+                for target in targets {
+                    let value = target.get_value_in_parent(state)?;
+                    if target.is_rest() {
+                        values.extend(value.as_a::<Vec<Value>>()?);
+                    } else {
+                        values.push(value);
+                    }
+                }
+                Ok(Value::from(values))
+            }
+            Self::Rest(target) => target.get_value_in_parent(state),
+            Self::DestructureObject(entries) => {
+                let mut values = vec![];
+                for (key, target, _) in entries {
+                    values.push((Value::from(key.as_str()), target.get_value_in_parent(state)?));
+                }
+                Ok(Value::try_from(values)?)
+            }
         }
     }
 
@@ -193,18 +313,17 @@ impl<'i> AssignmentTarget<'i> {
                 Ok(())
             }
             Self::Destructure(targets) => {
-                if targets.len() != value.len() {
-                    oops!(DestructuringAssignment {
-                        expected_length: targets.len(),
-                        actual_length: value.len()
-                    })
-                } else {
-                    let values = value.as_a::<Vec<Value>>()?;
-                    for (target, value) in targets.iter().zip(values.into_iter()) {
-                        target.update_value(state, value)?;
-                    }
-                    Ok(())
+                for (target, value) in Self::destructure(targets, value)? {
+                    target.update_value(state, value)?;
+                }
+                Ok(())
+            }
+            Self::Rest(target) => target.update_value(state, value),
+            Self::DestructureObject(entries) => {
+                for (target, value) in Self::destructure_object(entries, value)? {
+                    target.update_value(state, value)?;
                 }
+                Ok(())
             }
         }
     }
@@ -233,7 +352,8 @@ impl<'i> AssignmentTarget<'i> {
                     .or_error(ErrorDetails::VariableName { name: base.clone() })?;
                 Some(Self::get_mut_index_handle(base, &idx)).transpose()
             }
-            Self::Destructure(_) => Ok(None),
+            Self::Destructure(_) | Self::DestructureObject(_) => Ok(None),
+            Self::Rest(target) => target.get_target_mut_in_parent(state),
         }
     }
 
@@ -269,18 +389,17 @@ impl<'i> AssignmentTarget<'i> {
                 Ok(())
             }
             Self::Destructure(targets) => {
-                if targets.len() != value.len() {
-                    oops!(DestructuringAssignment {
-                        expected_length: targets.len(),
-                        actual_length: value.len()
-                    })
-                } else {
-                    let values = value.as_a::<Vec<Value>>()?;
-                    for (target, value) in targets.iter().zip(values.into_iter()) {
-                        target.update_value_in_parent(state, value)?;
-                    }
-                    Ok(())
+                for (target, value) in Self::destructure(targets, value)? {
+                    target.update_value_in_parent(state, value)?;
                 }
+                Ok(())
+            }
+            Self::Rest(target) => target.update_value_in_parent(state, value),
+            Self::DestructureObject(entries) => {
+                for (target, value) in Self::destructure_object(entries, value)? {
+                    target.update_value_in_parent(state, value)?;
+                }
+                Ok(())
             }
         }
     }
@@ -326,6 +445,16 @@ impl<'i> AssignmentTarget<'i> {
                     .collect::<Result<Vec<_>, _>>()?;
                 Ok(Value::from(results))
             }
+
+            AssignmentTarget::Rest(target) => target.delete(state),
+
+            AssignmentTarget::DestructureObject(entries) => {
+                let mut results = vec![];
+                for (key, target, _) in entries {
+                    results.push((Value::from(key.as_str()), target.delete(state)?));
+                }
+                Ok(Value::try_from(results)?)
+            }
         }
     }
 }
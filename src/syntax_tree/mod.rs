@@ -8,4 +8,5 @@ mod pratt;
 pub mod traits;
 
 pub use assignment_target::AssignmentTarget;
+pub use pratt::{all_operators, OperatorInfo, PrattOperatorType};
 pub use nodes::Node;
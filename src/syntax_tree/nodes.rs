@@ -54,7 +54,7 @@ pub enum Node<'i> {
     /// Collections (arrays, objects, ranges, etc.)
     Collections(Box<collections::Collections<'i>>),
 
-    /// Conditional expressions (if, ternary, switch)
+    /// Conditional expressions (if, ternary, switch, try)
     Conditionals(Box<conditionals::Conditionals<'i>>),
 
     /// Iterators (for loops)
@@ -111,6 +111,11 @@ impl Node<'_> {
                 literals::FloatLiteral::build(pairs, token, state)
             }
             Rule::string_literal => literals::StringLiteral::build(pairs, token, state),
+            Rule::byte_string_literal => literals::ByteStringLiteral::build(pairs, token, state),
+            Rule::raw_string_literal => literals::RawStringLiteral::build(pairs, token, state),
+            Rule::interpolated_string_literal => {
+                values::InterpolatedString::build(pairs, token, state)
+            }
             Rule::bool_literal => literals::BoolLiteral::build(pairs, token, state),
             Rule::regex_literal => literals::RegexLiteral::build(pairs, token, state),
             Rule::fixed_literal => literals::FixedLiteral::build(pairs, token, state),
@@ -134,7 +139,9 @@ impl Node<'_> {
             //
             // Collection nodes
             Rule::ARRAY_TERM => collections::Array::build(pairs, token, state),
+            Rule::SPREAD_EXPRESSION => collections::Spread::build(pairs, token, state),
             Rule::OBJECT_TERM => collections::Object::build(pairs, token, state),
+            Rule::OBJECT_PATTERN => assignment::ObjectPattern::build(pairs, token, state),
             Rule::OP_RANGE => collections::Range::build(pairs, token, state),
             Rule::POSTFIX_INDEX => collections::IndexingExpression::build(pairs, token, state),
 
@@ -149,6 +156,7 @@ impl Node<'_> {
             Rule::IF_EXPRESSION => conditionals::IfExpression::build(pairs, token, state),
             Rule::OP_TERNARY => conditionals::TernaryExpression::build(pairs, token, state),
             Rule::SWITCH_EXPRESSION => conditionals::SwitchExpression::build(pairs, token, state),
+            Rule::TRY_EXPRESSION => conditionals::TryExpression::build(pairs, token, state),
 
             //
             // Arithmetic
@@ -224,6 +232,49 @@ impl Node<'_> {
             _ => panic!("No node builder for rule {:?}", token.rule),
         })
     }
+
+    /// Flags calls to the given function that are in tail position
+    /// Used by user-defined functions to loop on self-recursion instead of growing the stack
+    pub(crate) fn mark_tail_calls(&mut self, name: &str) {
+        match self {
+            Self::Core(node) => {
+                if let core::Core::Block(block) = node.as_mut() {
+                    if let Some(last) = block.statements.last_mut() {
+                        last.mark_tail_calls(name);
+                    }
+                }
+            }
+
+            Self::Conditionals(node) => match node.as_mut() {
+                conditionals::Conditionals::IfExpression(node) => {
+                    node.then_branch.mark_tail_calls(name);
+                    node.else_branch.mark_tail_calls(name);
+                }
+                conditionals::Conditionals::SwitchExpression(node) => {
+                    for case in node.cases.iter_mut() {
+                        match case {
+                            conditionals::SwitchCase::Default(body)
+                            | conditionals::SwitchCase::Case(_, _, body) => body.mark_tail_calls(name),
+                        }
+                    }
+                }
+                conditionals::Conditionals::TryExpression(node) => {
+                    // The body is not in tail position, since its errors are caught here
+                    node.handler.mark_tail_calls(name);
+                }
+            },
+
+            Self::Functions(node) => match node.as_mut() {
+                functions::Functions::FunctionCall(node) if node.name == name => {
+                    node.tail_call = true;
+                }
+                functions::Functions::KeywordReturn(node) => node.value.mark_tail_calls(name),
+                _ => {}
+            },
+
+            _ => {}
+        }
+    }
 }
 impl IntoOwned for Node<'_> {
     type Owned = Node<'static>;
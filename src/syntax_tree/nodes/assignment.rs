@@ -1,4 +1,4 @@
-use super::Node;
+use super::{collections::Collections, values::Values, Node};
 use crate::{
     error::{ErrorDetails, WrapExternalError, WrapOption},
     syntax_tree::{
@@ -15,6 +15,50 @@ use polyvalue::{
     Value,
 };
 
+impl<'i> AssignmentTarget<'i> {
+    /// Attempt to convert a node into an assignment target
+    /// Arrays of references become destructuring assignments, which can be nested,
+    /// and may contain a single spread element to collect the remaining values
+    pub(crate) fn from_node(node: Node<'i>) -> Option<Self> {
+        match node {
+            Node::Values(node) => match *node {
+                Values::Reference(node) => Some(node.target),
+                _ => None,
+            },
+            Node::Assignment(node) => match *node {
+                Assignment::ObjectPattern(node) => Some(node.target),
+                _ => None,
+            },
+            Node::Collections(node) => match *node {
+                Collections::Array(array) => {
+                    let targets = array
+                        .elements
+                        .into_iter()
+                        .map(|e| match e {
+                            Node::Collections(node) => match *node {
+                                Collections::Spread(spread) => {
+                                    Self::from_node(spread.value).map(|t| Self::Rest(Box::new(t)))
+                                }
+                                node => Self::from_node(Node::Collections(Box::new(node))),
+                            },
+                            e => Self::from_node(e),
+                        })
+                        .collect::<Option<Vec<_>>>()?;
+
+                    // Only one rest element is allowed per array
+                    if targets.iter().filter(|t| t.is_rest()).count() > 1 {
+                        return None;
+                    }
+
+                    Some(Self::Destructure(targets))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 #[rustfmt::skip]
 pub enum AssignmentOperation {
@@ -76,28 +120,23 @@ impl AssignmentOperation {
     ) -> Result<Value, Error> {
         match &target {
             // Assign a single value to multiple targets
-            AssignmentTarget::Destructure(targets) if rhs.len() == 1 => {
+            AssignmentTarget::Destructure(targets)
+                if rhs.len() == 1 && !targets.iter().any(|t| t.is_rest()) =>
+            {
                 for target in targets {
-                    self.apply_to(state, target, rhs.clone())?;
+                    self.apply(state, target, rhs.clone())?;
                 }
                 target.get_value(state)
             }
 
-            // Assign multiple values to multiple targets
-            AssignmentTarget::Destructure(targets) if rhs.len() == targets.len() => {
-                let rhs = rhs.as_a::<Vec<Value>>()?;
-                for (target, value) in targets.iter().zip(rhs) {
-                    self.apply_to(state, target, value)?;
+            // Assign multiple values to multiple, possibly nested, targets
+            AssignmentTarget::Destructure(targets) => {
+                for (target, value) in AssignmentTarget::destructure(targets, rhs)? {
+                    self.apply(state, target, value)?;
                 }
                 target.get_value(state)
             }
 
-            // Target count mismatch
-            AssignmentTarget::Destructure(targets) => oops!(DestructuringAssignment {
-                expected_length: targets.len(),
-                actual_length: rhs.len()
-            }),
-
             // Assign a single value to a single target
             _ => self.apply_to(state, target, rhs),
         }
@@ -133,7 +172,7 @@ define_ast!(
                 let target = unwrap_next!(pairs, token);
 
                 let target = target.into_node(state).with_context(&token)?;
-                let mut target = as_assignment_target!(target).or_error(ErrorDetails::ConstantValue).with_context(&token)?;
+                let mut target = AssignmentTarget::from_node(target).or_error(ErrorDetails::ConstantValue).with_context(&token)?;
 
                 match target {
                     AssignmentTarget::Identifier(ref mut id) => {
@@ -196,7 +235,7 @@ define_ast!(
                 let op = AssignmentOperation::from(unwrap_next!(pairs, token).as_rule());
                 let rhs = unwrap_node!(pairs, state, token)?;
 
-                let target = as_assignment_target!(lhs).or_error(ErrorDetails::ConstantValue).with_context(&token)?;
+                let target = AssignmentTarget::from_node(lhs).or_error(ErrorDetails::ConstantValue).with_context(&token)?;
                 Ok(Self { target, op, rhs, token }.into())
             },
             eval = (this, state) {
@@ -219,6 +258,7 @@ define_ast!(
                     Target is either a literal with optional indices, or a destructuring assignment
                     If an index is empty, a new value will be appended to the array
                     If the target is a destructuring assignment, the value must be a collection of the same length
+                    Destructuring assignments can be nested, and may contain one `...rest` element to collect any remaining values
                     If the operator is present, the value will be transformed before assignment
 
                     Operators:
@@ -230,10 +270,67 @@ define_ast!(
                 ",
                 examples: "
                     [a, b] = [1, 2]     // Destructuring assignment
+                    [a, [b, c]] = [1, [2, 3]]   // Nested destructuring
+                    [a, ...b] = [1, 2, 3]       // b = [2, 3]
                     a = 1; a += 1       // Arithmetic assignment
                     a = [1]; a[] = 2    // Array index assignment (appends to array)
                 ",
             }
+        },
+
+        ObjectPattern(target: AssignmentTarget<'i>) {
+            build = (pairs, token, state) {
+                let mut entries = vec![];
+                for entry in pairs {
+                    let mut entry = entry.into_inner();
+                    let key = unwrap_next!(entry, token).as_str().to_string();
+
+                    let optional = entry.peek().map(|p| p.as_rule()) == Some(Rule::object_pattern_optional);
+                    if optional {
+                        entry.next();
+                    }
+
+                    let target = match entry.next() {
+                        Some(target) => {
+                            let target = target.into_node(state).with_context(&token)?;
+                            AssignmentTarget::from_node(target).or_error(ErrorDetails::ConstantValue).with_context(&token)?
+                        }
+                        None => AssignmentTarget::Identifier(key.clone()),
+                    };
+
+                    entries.push((key, target, optional));
+                }
+
+                Ok(Self { target: AssignmentTarget::DestructureObject(entries), token }.into())
+            },
+            eval = (this, state) {
+                this.target.get_value(state).with_context(this.token())
+            },
+            owned = (this) {
+                Self::Owned {
+                    target: this.target.into_owned(),
+                    token: this.token.into_owned(),
+                }
+            },
+            docs = {
+                name: "Object Destructuring",
+                symbols = ["{ a, b: c, d? } = ..."],
+                description: "
+                    Assigns variables from the matching keys of an object
+                    `key: target` assigns the value of `key` to a different variable, or to a nested pattern
+                    A missing key is an error, unless the key is marked optional with `?`, in which case nil is assigned
+                ",
+                examples: "
+                    {a, b: c} = {'a': 1, 'b': 2}
+                    assert_eq([a, c], [1, 2])
+
+                    {a, missing?} = {'a': 1}
+                    assert_eq(missing, nil)
+
+                    {a: {b}} = {'a': {'b': 3}}
+                    assert_eq(b, 3)
+                ",
+            }
         }
     }
 );
@@ -293,6 +390,40 @@ mod test {
         [a, b] = [1, 2, 3]
     "#);
 
+    lav!(test_assign_destructure_rest r#"
+        [a, ...b] = [1, 2, 3]
+        assert_eq(a, 1)
+        assert_eq(b, [2, 3])
+
+        [a, ...b, c] = [1, 2]
+        assert_eq([a, b, c], [1, [], 2])
+
+        [a, [b, ...c]] = [1, [2, 3, 4]]
+        assert_eq([a, b, c], [1, 2, [3, 4]])
+
+        assert_eq([a, ...b] = [5, 6, 7], [5, 6, 7])
+    "#);
+
+    lav!(test_assign_destructure_rest_error(Error) r#"
+        [a, b, ...c] = [1]
+    "#);
+
+    lav!(test_assign_destructure_object r#"
+        {a, b: c} = {'a': 1, 'b': 2, 'd': 4}
+        assert_eq(a, 1)
+        assert_eq(c, 2)
+
+        {x: [y, z], w?} = {'x': [5, 6]}
+        assert_eq([y, z, w], [5, 6, nil])
+
+        {a: {b}} = {'a': {'b': 3}}
+        assert_eq(b, 3)
+    "#);
+
+    lav!(test_assign_destructure_object_error(Error) r#"
+        {a, missing} = {'a': 1}
+    "#);
+
     lav!(test_buggy_push r#"
         save = {'choices':[]}; choice = 5
         save['choices'].push(choice)
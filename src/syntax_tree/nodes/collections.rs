@@ -1,7 +1,10 @@
 use super::{values::Reference, Node};
 use crate::{
     error::{ErrorDetails, WrapExternalError, WrapOption},
-    syntax_tree::{assignment_target::AssignmentTarget, traits::IntoNode},
+    syntax_tree::{
+        assignment_target::AssignmentTarget,
+        traits::{IntoNode, NodeExt},
+    },
     Error, Rule,
 };
 use polyvalue::{Value, ValueType};
@@ -17,11 +20,7 @@ define_ast!(
                 Ok(Self { elements, token }.into())
             },
             eval = (this, state) {
-                let elements = this
-                    .elements
-                    .iter()
-                    .map(|element| element.evaluate(state))
-                    .collect::<Result<Vec<_>, _>>().with_context(this.token())?;
+                let elements = Spread::evaluate_all(&this.elements, state).with_context(this.token())?;
                 Ok(Value::array(elements))
             },
             owned = (this) {
@@ -48,13 +47,70 @@ define_ast!(
             }
         },
 
+        Spread(value: Node<'i>) {
+            build = (pairs, token, state) {
+                let value = unwrap_node!(pairs, state, token)?;
+                Ok(Self { value, token }.into())
+            },
+            eval = (this, state) {
+                let value = this.value.evaluate(state).with_context(this.token())?;
+                if value.own_type() != ValueType::Array {
+                    return oops!(SpreadType {
+                        actual_type: value.own_type()
+                    }, this.token().clone());
+                }
+                Ok(value)
+            },
+            owned = (this) {
+                Self::Owned {
+                    value: this.value.into_owned(),
+                    token: this.token.into_owned(),
+                }
+            },
+
+            docs = {
+                name: "Spread",
+                symbols = ["[ ...a, b ]", "f(...a)"],
+                description: "
+                    Expands an array in-place, inside an array literal or the arguments of a function call.
+                    Spreading any value other than an array is an error.
+                ",
+                examples: "
+                    a = [1, 2]
+                    assert_eq([...a, 3, ...[4, 5]], [1, 2, 3, 4, 5])
+                    assert_eq(len(...[[1, 2]]), 2)
+                    assert( would_err('[...5]') )
+                ",
+            }
+        },
+
         Object(entries: Vec<(Node<'i>, Node<'i>)>) {
             build = (pairs, token, state) {
                 let mut entries: Vec<(_, _)> = Vec::new();
                 while let Some(key) = pairs.next() {
-                    let key = key.into_node(state).with_context(&token)?;
-                    let value = unwrap_node!(pairs, state, token)?;
-                    entries.push((key, value));
+                    match key.as_rule() {
+                        // `{x}` is shorthand for `{'x': x}`
+                        Rule::object_shorthand => {
+                            let name = Node::Literal(Value::from(key.as_str().to_string()), key.token().clone());
+                            let mut key = key.into_inner();
+                            let value = unwrap_node!(key, state, token)?;
+                            entries.push((name, value));
+                        }
+
+                        // `{[expr]: value}` uses the result of expr as the key
+                        Rule::object_computed_key => {
+                            let mut key = key.into_inner();
+                            let key = unwrap_node!(key, state, token)?;
+                            let value = unwrap_node!(pairs, state, token)?;
+                            entries.push((key, value));
+                        }
+
+                        _ => {
+                            let key = key.into_node(state).with_context(&token)?;
+                            let value = unwrap_node!(pairs, state, token)?;
+                            entries.push((key, value));
+                        }
+                    }
                 }
 
                 Ok(Self { entries, token }.into())
@@ -74,16 +130,24 @@ define_ast!(
 
             docs = {
                 name: "Object Literals",
-                symbols = ["{ key: value, ... }"],
+                symbols = ["{ key: value, ... }", "{ name, ... }", "{ [key]: value, ... }"],
                 description: "
                     A collection of key-value pairs.
                     Values can contain any type, including other objects.
                     Keys can be any non-collection type
                     The indexing operator (a[b]) can be used to access elements of an object.
+                    A bare identifier is shorthand for an entry keyed by its name: `{x}` is `{'x': x}`.
+                    A key in square brackets is evaluated, and its result used as the key: `{[k]: v}`.
                 ",
                 examples: "
                     { \"name\": \"John\", \"age\": 25 }
                     { \"name\": \"John\", \"address\": { \"city\": \"New York\", \"state\": \"NY\" } }
+
+                    name = 'John'
+                    assert_eq({name, 'age': 25}, {'name': 'John', 'age': 25})
+
+                    k = 'a'
+                    assert_eq({[k + 'b']: 1}, {'ab': 1})
                 ",
             }
         },
@@ -258,3 +322,20 @@ define_ast!(
         }
     }
 );
+
+impl<'i> Spread<'i> {
+    /// Evaluates a list of nodes, expanding any spread nodes in-place
+    pub fn evaluate_all(nodes: &[Node<'i>], state: &mut crate::State) -> Result<Vec<Value>, Error> {
+        let mut values = Vec::with_capacity(nodes.len());
+        for node in nodes {
+            match node {
+                Node::Collections(inner) if matches!(inner.as_ref(), Collections::Spread(_)) => {
+                    values.extend(node.evaluate(state)?.as_a::<Vec<Value>>()?);
+                }
+                _ => values.push(node.evaluate(state)?),
+            }
+            state.check_output_size(values.len())?;
+        }
+        Ok(values)
+    }
+}
@@ -4,7 +4,7 @@ use crate::{
     functions::{ParserFunction, UserDefinedFunction},
     syntax_tree::{
         pair::Reiterate,
-        traits::{IntoNode, IntoOwned},
+        traits::{IntoNode, IntoOwned, NodeExt},
     },
     Error, Rule, Token,
 };
@@ -24,6 +24,7 @@ define_ast!(
                 let parts = pairs.filter(|p| {
                     p.as_rule() != Rule::if_keyword &&
                     p.as_rule() != Rule::else_keyword &&
+                    p.as_rule() != Rule::elif_keyword &&
                     p.as_rule() != Rule::then_keyword
                 }).collect::<Vec<_>>();
                 let mut pairs = parts.reiterate();
@@ -76,13 +77,14 @@ define_ast!(
 
             docs = {
                 name: "If",
-                symbols = ["if <condition> then <block> else <block>", "if <condition> {block} else {block}"],
+                symbols = ["if <condition> then <block> else <block>", "if <condition> {block} else {block}", "if <condition> {block} elif <condition> {block} else {block}"],
                 description: "
                     A conditional expression that evaluates a condition and then one of two branches.
                     body can be either a block or a single expression. The last expression is returned from a block.
                     Since all expressions in lavendeux return a value, the if expression will return the value of the branch that is executed.
                     As such, all if expressions must have both a then and an else branch.
                     If a condition doesn't need to return a value (side-effect `if`s for example), use `nil`
+                    Conditions can be chained with `else if`, or the equivalent `elif`; `then` and block forms can be mixed freely in a chain
                 ",
                 examples: "
                     a = 6
@@ -92,6 +94,7 @@ define_ast!(
                     } else if a == 5 {
                         5
                     } else nil
+                    if a < 0 then 'negative' elif a == 0 { 'zero' } elif a < 10 then 'small' else { 'large' }
                 ",
             }
         },
@@ -106,17 +109,51 @@ define_ast!(
                 let mut cases = vec![];
 
                 while let Some(case) = pairs.next()  {
+                    let guard = match pairs.peek() {
+                        Some(p) if p.as_rule() == Rule::SWITCH_GUARD => {
+                            let mut guard = unwrap_next!(pairs, token).into_inner();
+                            guard.next(); // Skip the if keyword
+                            Some(unwrap_node!(guard, state, token)?)
+                        },
+                        _ => None
+                    };
                     let body = unwrap_node!(pairs, state, token)?;
 
-                    if case.as_str() == "_" {
+                    if case.as_str() == "_" && guard.is_none() {
                         cases.push(SwitchCase::Default(body));
                         if pairs.next().is_some() {
                             return oops!(UnreachableSwitchCase, token);
                         }
 
                         break;
-                    } else {
-                        cases.push(SwitchCase::Case(case.into_node(state).with_context(&token)?, body));
+                    }
+
+                    let pattern = match case.as_rule() {
+                        _ if case.as_str() == "_" => SwitchPattern::Any,
+                        Rule::SWITCH_BINDING => {
+                            let mut binding = case.into_inner();
+                            binding.next(); // Skip the as keyword
+                            SwitchPattern::Binding(unwrap_next!(binding, token).as_str().to_string())
+                        }
+                        Rule::SWITCH_RANGE => {
+                            let mut range = case.into_inner();
+                            let start = unwrap_node!(range, state, token)?;
+                            range.next(); // Skip the '..'
+                            let end = unwrap_node!(range, state, token)?;
+                            SwitchPattern::Range(start, end)
+                        }
+                        _ => SwitchPattern::Value(case.into_node(state).with_context(&token)?),
+                    };
+
+                    // An unguarded binding matches everything, so it acts as the default case
+                    let is_default = guard.is_none() && matches!(pattern, SwitchPattern::Binding(_));
+                    cases.push(SwitchCase::Case(pattern, guard, body));
+                    if is_default {
+                        if pairs.next().is_some() {
+                            return oops!(UnreachableSwitchCase, token);
+                        }
+
+                        break;
                     }
                 }
 
@@ -139,18 +176,28 @@ define_ast!(
                             return result;
                         },
 
-                        SwitchCase::Case(value, body) => {
-                            let value = value.evaluate(state).with_context(this.token())?;
+                        SwitchCase::Case(pattern, guard, body) => {
+                            if pattern.matches(&match_on, state).with_context(this.token())? {
+                                state.scope_into().with_context(this.token())?;
+                                if let SwitchPattern::Binding(name) = pattern {
+                                    state.set_variable(name, match_on.clone());
+                                }
 
-                            if value.own_type() != match_on.own_type() {
-                                return oops!(SwitchCaseTypeMismatch {
-                                    case: value,
-                                    expected_type: match_on.own_type()
-                                }, this.token.clone());
-                            }
+                                // A failed guard falls through to the next case
+                                if let Some(guard) = guard {
+                                    match guard.evaluate(state) {
+                                        Ok(v) if v.is_truthy() => {},
+                                        Ok(_) => {
+                                            state.scope_out();
+                                            continue;
+                                        },
+                                        Err(e) => {
+                                            state.scope_out();
+                                            return Err(e);
+                                        }
+                                    }
+                                }
 
-                            if value == match_on {
-                                state.scope_into().with_context(this.token())?;
                                 let result = body.evaluate(state);
 
                                 state.scope_out();
@@ -175,6 +222,9 @@ define_ast!(
                 description: "
                     A conditional expression that evaluates a value and then one of several cases.
                     match blocks must be exhaustive, and therefore must end in a default case
+                    A case can be a value, a variable to compare against, an inclusive range of values (`1..10`), or a binding
+                    A binding (`_ as name`) matches any value, and binds it to that name within the case's block, acting as the default case
+                    Any case can be followed by an `if` guard; if the guard is false, matching continues with the next case
                 ",
                 examples: "
                     a = 6
@@ -183,6 +233,106 @@ define_ast!(
                         6 => { 'six' },
                         _ => { 'other' }
                     }
+                    match a {
+                        0..5 => 'small',
+                        6..10 => 'medium',
+                        _ as n => n * 2
+                    }
+                    match a {
+                        _ as n if n < 0 => 'negative',
+                        _ as n if n % 2 == 0 => 'even',
+                        _ => 'odd'
+                    }
+                ",
+            }
+        },
+
+        TryExpression(
+            body: Node<'i>,
+            binding: Option<String>,
+            handler: Node<'i>
+        ) {
+            build = (pairs, token, state) {
+                pairs.next(); // Skip the try keyword
+                let body = unwrap_node!(pairs, state, token)?;
+                pairs.next(); // Skip the catch keyword
+
+                let binding = match pairs.peek() {
+                    Some(p) if p.as_rule() == Rule::catch_binding => {
+                        Some(unwrap_next!(pairs, token).as_str().to_string())
+                    },
+                    _ => None,
+                };
+
+                // Binding the error to `e` shadows the constant within the handler
+                let handler = unwrap_next!(pairs, token);
+                let shadows = binding.as_deref() == Some("e");
+                if shadows {
+                    state.shadow_constant("e");
+                }
+                let handler = handler.into_node(state).with_context(&token);
+                if shadows {
+                    state.unshadow_constant();
+                }
+                let handler = handler?;
+                Ok(Self { body, binding, handler, token }.into())
+            },
+            eval = (this, state) {
+                let depth = state.current_depth();
+                let error = match this.body.evaluate(state) {
+                    Ok(value) => return Ok(value),
+                    Err(e) => e,
+                };
+
+                // Control flow is not an error, and timeouts must not be swallowed
+                // Errors raised inside a function call are wrapped, so the original cause is checked
+                if matches!(
+                    error.root_cause().details,
+                    ErrorDetails::Return { .. } | ErrorDetails::TailCall { .. }
+                        | ErrorDetails::Skip { .. } | ErrorDetails::Break { .. }
+                        | ErrorDetails::Timeout
+                ) {
+                    return Err(error);
+                }
+
+                // Unwind any scopes left open by the failed expression
+                while state.current_depth() > depth {
+                    state.scope_out();
+                }
+
+                state.scope_into().with_context(this.token())?;
+                if let Some(name) = &this.binding {
+                    state.set_variable(name, Value::from(&error));
+                }
+                let result = this.handler.evaluate(state);
+
+                state.scope_out();
+                result
+            },
+            owned = (this) {
+                Self::Owned {
+                    body: this.body.into_owned(),
+                    binding: this.binding,
+                    handler: this.handler.into_owned(),
+                    token: this.token.into_owned(),
+                }
+            },
+            docs = {
+                name: "Try",
+                symbols = ["try <block> catch <block>", "try <block> catch <name> { <block> }"],
+                description: "
+                    Evaluates a block, and evaluates the catch block instead if it raises an error.
+                    If a name is given, the error is bound to it as an object with the keys `kind`, `message`, `line` and `token`
+                    `kind` is the type of error raised, such as 'VariableName' or 'Overflow'
+                    The name `e` can be used, and shadows the constant within the catch block
+                ",
+                examples: "
+                    assert_eq(try { asparagus } catch e { e['kind'] }, 'VariableName')
+                    assert_eq(try { 1 / 0 } catch err { e }, e)
+                    assert_eq(try { 1 / 0 } catch 'oops', 'oops')
+                    assert_eq(try 5 catch 0, 5)
+                    assert_eq(try { asparagus } catch err { err['kind'] }, 'VariableName')
+                    try { error('bad input') } catch err { assert_eq(err['message'], 'bad input') }
                 ",
             }
         }
@@ -192,17 +342,64 @@ define_ast!(
 #[derive(Debug, Clone)]
 pub enum SwitchCase<'i> {
     Default(Node<'i>),
-    Case(Node<'i>, Node<'i>),
+    Case(SwitchPattern<'i>, Option<Node<'i>>, Node<'i>), // (pattern, guard, body)
 }
 impl IntoOwned for SwitchCase<'_> {
     type Owned = SwitchCase<'static>;
     fn into_owned(self) -> Self::Owned {
         match self {
             Self::Default(node) => Self::Owned::Default(node.into_owned()),
-            Self::Case(condition, body) => {
-                Self::Owned::Case(condition.into_owned(), body.into_owned())
+            Self::Case(pattern, guard, body) => Self::Owned::Case(
+                pattern.into_owned(),
+                guard.map(|g| g.into_owned()),
+                body.into_owned(),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SwitchPattern<'i> {
+    Any,
+    Value(Node<'i>),
+    Range(Node<'i>, Node<'i>),
+    Binding(String),
+}
+impl IntoOwned for SwitchPattern<'_> {
+    type Owned = SwitchPattern<'static>;
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::Any => Self::Owned::Any,
+            Self::Value(node) => Self::Owned::Value(node.into_owned()),
+            Self::Range(start, end) => Self::Owned::Range(start.into_owned(), end.into_owned()),
+            Self::Binding(name) => Self::Owned::Binding(name),
+        }
+    }
+}
+impl SwitchPattern<'_> {
+    /// Returns true if the given value matches this pattern
+    /// Values and range bounds must be of the same type as the value being matched
+    pub fn matches(&self, match_on: &Value, state: &mut crate::State) -> Result<bool, Error> {
+        let bounds = match self {
+            Self::Any | Self::Binding(_) => return Ok(true),
+            Self::Value(value) => vec![value.evaluate(state)?],
+            Self::Range(start, end) => vec![start.evaluate(state)?, end.evaluate(state)?],
+        };
+
+        for bound in &bounds {
+            if bound.own_type() != match_on.own_type() {
+                return oops!(SwitchCaseTypeMismatch {
+                    case: bound.clone(),
+                    expected_type: match_on.own_type()
+                });
             }
         }
+
+        Ok(match bounds.as_slice() {
+            [value] => value == match_on,
+            [start, end] => start <= match_on && match_on <= end,
+            _ => false,
+        })
     }
 }
 
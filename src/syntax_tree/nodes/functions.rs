@@ -1,4 +1,4 @@
-use super::Node;
+use super::{collections::Spread, Node};
 use crate::{
     error::{ErrorDetails, WrapExternalError},
     functions::{ParserFunction, UserDefinedFunction},
@@ -42,7 +42,7 @@ define_ast!(
             }
         },
 
-        FunctionCall(name: String, arguments: Vec<Node<'i>>) {
+        FunctionCall(name: String, arguments: Vec<Node<'i>>, tail_call: bool, namespace: Option<String>) {
             build = (pairs, token, state) {
                 let lhs = unwrap_next!(pairs, token); // Function name, or the first argument of an object mode call
                 let mut rhs = unwrap_next!(
@@ -62,7 +62,7 @@ define_ast!(
                             rhs.map(|p| p.into_node(state)).collect::<Result<Vec<_>, _>>().with_context(&token)?
                         };
 
-                        Self { name, arguments, token }
+                        Self { name, arguments, tail_call: false, namespace: None, token }
                     }
 
                     // Rule::POSTFIX_OBJECTMODE
@@ -70,13 +70,19 @@ define_ast!(
                         let mut rhs = rhs;
                         let name = unwrap_next!(rhs, token).as_str().to_string();
 
+                        // `a.f()` may also name a function imported under the namespace `a`
+                        let namespace = match lhs.as_rule() {
+                            Rule::identifier => Some(lhs.as_str().to_string()),
+                            _ => None,
+                        };
+
                         let rhs = unwrap_next!(rhs, token);
                         let arguments = vec![lhs.into_node(state)] // First argument
                             .into_iter()
                             .chain(rhs.map(|p| p.into_node(state)))
                             .collect::<Result<Vec<_>, _>>().with_context(&token)?;
 
-                        Self { name, arguments, token }
+                        Self { name, arguments, tail_call: false, namespace, token }
                     }
                 };
 
@@ -93,24 +99,39 @@ define_ast!(
                     return Ok(Value::from(help_text));
                 }
 
-                // Collect arguments
-                let mut arguments = Vec::new();
-                for argument in this.arguments.iter() {
-                    arguments.push(argument.evaluate(state).with_context(this.token())?);
+                // Calls to imported functions name the namespace in place of a first argument
+                let qualified_name = this.namespace.as_ref()
+                    .map(|namespace| format!("{namespace}.{}", this.name))
+                    .filter(|name| state.get_function(name).is_some());
+                let (name, arguments) = match &qualified_name {
+                    Some(name) => (name.as_str(), &this.arguments[1..]),
+                    None => (this.name.as_str(), &this.arguments[..]),
+                };
+
+                // Collect arguments, expanding any spread arrays
+                let argument_values = Spread::evaluate_all(arguments, state).with_context(this.token())?;
+
+                // Self-recursive calls in tail position are handed back to the calling function
+                // which will loop instead of growing the stack
+                if this.tail_call {
+                    return oops!(TailCall { arguments: argument_values }, this.token().clone());
                 }
 
                 // Update reference argument
-                let reference = this.arguments.first().and_then(|arg1| {
+                let reference = arguments.first().and_then(|arg1| {
                     as_reference!(arg1.clone())
                 });
 
-                let value = match state.call_function(&this.name, arguments, reference.as_ref()) {
+                let result = state.call_function(name, argument_values, reference.as_ref());
+                state.locate_warnings(this.token());
+
+                let value = match result {
                     Ok(value) => value,
                     Err(e) => {
                         if let ErrorDetails::Return { value, .. } = e.details {
                             value
                         } else {
-                            return Err(ErrorDetails::FunctionCall { name: this.name.clone() })
+                            return Err(ErrorDetails::FunctionCall { name: name.to_string() })
                             .with_context(this.token())
                             .with_source(e)
                         }
@@ -126,6 +147,8 @@ define_ast!(
                         .into_iter()
                         .map(|s| s.into_owned())
                         .collect(),
+                    tail_call: this.tail_call,
+                    namespace: this.namespace,
                     token: this.token.into_owned(),
                 }
             },
@@ -138,6 +161,7 @@ define_ast!(
                     The help() will list all available functions, and can filter by category or function name.
 
                     Functions can be called as `name(arg1, arg2, ...)` or `arg1.func(arg2, arg3, ...)`.
+                    Functions loaded with import() are called as `namespace.func(arg1, arg2, ...)`.
                     Some stdlib functions such as `push`, `pop`, etc take the 1st argument as a reference.
                     In every other case, all arguments are passed by value.
                 ",
@@ -209,7 +233,7 @@ define_handler!(
 document_operator!(
     name = "Function Assignment",
     rules = [],
-    symbols = ["name([arg1:type, arg2, ...]) = { ... }"],
+    symbols = ["name([arg1:type, arg2, ...]) = { ... }", "name(...) -> type = { ... }"],
     description = "
         Assigns a block of code to a function name.
         The function can be called later in the code.
@@ -217,8 +241,11 @@ document_operator!(
 
         Function body can be a block of code or a single expression. The last expression is returned, unless a return statement is used.
         Return type or argued types can be specified with `: type`, but are optional.
+        The return type can also be written as `-> type`.
 
         Arguments will be cooerced to the specified type if provided, as will the return value.
+        A value that cannot be coerced to the return type raises an error.
+        A function calling itself as its final expression (a tail call) will loop instead of recursing, and so will not run out of stack.
         Valid type names are: `u[8-64]`, `i[8-64]`, `float`, `int`, `numeric`, `string`, `array`, `object`, `bool`, `any`.
     ",
     examples = "
@@ -231,5 +258,8 @@ document_operator!(
             a + b
         }
         add(3, 4.5)
+
+        half(x) -> float = x / 2.0
+        half(3)
     ",
 );
@@ -13,22 +13,32 @@ use polyvalue::{
 
 define_ast!(
     Iterators {
-        KeywordContinue() {
-            build = (_pairs, token, _state) {
-                Ok(Self { token }.into())
+        KeywordContinue(value: Option<Node<'i>>) {
+            build = (pairs, token, _state) {
+                pairs.next(); // Skip the continue keyword
+                let value = pairs.next().map(|p| p.into_node(_state)).transpose()?;
+                Ok(Self { value, token }.into())
             },
-            eval = (this, _state) {
-                oops!(Skip, this.token.clone())
+            eval = (this, state) {
+                let value = this.value.clone().map(|v| v.evaluate(state)).transpose()?;
+                oops!(Skip { value }, this.token.clone())
             },
             owned = (this) {
-                Self::Owned { token: this.token.into_owned() }
+                Self::Owned {
+                    value: this.value.map(|v| v.into_owned()),
+                    token: this.token.into_owned(),
+                }
             },
             docs = {
                 name: "Continue",
-                symbols = ["continue"],
-                description: "Skips the current iteration of a loop",
+                symbols = ["continue", "continue <value>", "skip [value]"],
+                description: "
+                    Skips the current iteration of a loop
+                    If a value is provided, it is added to the loop's results before moving on
+                ",
                 examples: "
                     for i in 0..10 { if i == 5 { continue } else {i} } 
+                    assert_eq(for i in 0..3 { if i == 1 { skip 'one' }; i }, [0, 'one', 2])
                 ",
             }
         },
@@ -51,31 +61,40 @@ define_ast!(
             },
             docs = {
                 name: "Break",
-                symbols = ["break"],
-                description: "Breaks out of a loop",
+                symbols = ["break", "break <value>"],
+                description: "
+                    Breaks out of a loop
+                    If a value is provided, it is added to the loop's results before exiting
+                ",
                 examples: "
                     for i in 0..10 { if i == 5 { break } else {i} }
+                    assert_eq(for i in 0..10 { if i == 2 { break 'done' }; i }, [0, 1, 'done'])
                 ",
             }
         },
 
         ForLoopExpression(
             variable: Option<String>,
+            value_variable: Option<String>,
             iterable: Node<'i>,
             body: Node<'i>,
-            condition: Option<Node<'i>>
+            condition: Option<Node<'i>>,
+            else_branch: Option<Node<'i>>
         ) {
             build = (pairs, token, state) {
                 pairs.next(); // Skip the for keyword
 
-                // Assignment
-                let variable = match pairs.peek() {
+                // Assignment, binding either one variable, or a key and a value
+                let (variable, value_variable) = match pairs.peek() {
                     Some(p) if p.as_rule() == Rule::for_assignment => {
                         let mut p = unwrap_next!(pairs, token);
-                        let p = unwrap_next!(p, token);
-                        Some(p.as_str().to_string())
+                        let variable = unwrap_next!(p, token).as_str().to_string();
+                        let value_variable = p
+                            .find(|p| p.as_rule() == Rule::identifier)
+                            .map(|p| p.as_str().to_string());
+                        (Some(variable), value_variable)
                     },
-                    _ => None,
+                    _ => (None, None),
                 };
 
                 // The actual iterable
@@ -101,30 +120,45 @@ define_ast!(
                     _ => None,
                 };
 
-                Ok(Self { variable, iterable, body, condition, token }.into())
+                // Else branch?
+                let else_branch = match pairs.peek() {
+                    Some(p) if p.as_rule() == Rule::for_else => {
+                        let mut p = unwrap_next!(pairs, token);
+                        p.next(); // Skip the else keyword
+                        Some(unwrap_node!(p, state, token)?)
+                    },
+                    _ => None,
+                };
+
+                Ok(Self { variable, value_variable, iterable, body, condition, else_branch, token }.into())
             },
 
             eval = (this, state) {
                 let iterable = this.iterable.evaluate(state).with_context(this.token())?;
+
+                // Entries are pairs of (key or index, value)
                 match iterable.own_type() {
                     ValueType::Range => {
+                        // Ranges are walked lazily, so the output limit applies before they are fully expanded
                         let iterable = iterable.as_a::<Range>().with_context(this.token())?.into_inner();
-                        let values = iterable.into_iter().map(|i| {
-                            state.check_timer()?;
-                            Ok::<_, Error>(Value::from(i))
-                        }).collect::<Result<Vec<_>, _>>().with_context(this.token())?;
-                        iterate_over(values.into_iter(), state, this)
+                        let entries = iterable.into_iter().enumerate().map(|(i, v)| (Value::from(i as i64), Value::from(v)));
+                        iterate_over(entries, false, state, this)
                     },
 
                     ValueType::Object => {
+                        // Keys are visited in sorted order, so that iteration is deterministic
                         let iterable = iterable.as_a::<Object>().with_context(this.token())?;
-                        let iterable = iterable.keys().into_iter().cloned();
-                        iterate_over(iterable, state, this)
+                        let mut entries = iterable.keys().into_iter().cloned()
+                            .zip(iterable.values().into_iter().cloned())
+                            .collect::<Vec<_>>();
+                        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                        iterate_over(entries.into_iter(), true, state, this)
                     },
 
                     _ => {
                         let iterable = iterable.as_a::<Vec<Value>>().with_context(this.token())?;
-                        iterate_over(iterable.into_iter(), state, this)
+                        let entries = iterable.into_iter().enumerate().map(|(i, v)| (Value::from(i as i64), v));
+                        iterate_over(entries, false, state, this)
                     }
                 }
             },
@@ -132,23 +166,28 @@ define_ast!(
             owned = (this) {
                 Self::Owned {
                     variable: this.variable,
+                    value_variable: this.value_variable,
                     iterable: this.iterable.into_owned(),
                     body: this.body.into_owned(),
                     condition: this.condition.map(|c| c.into_owned()),
+                    else_branch: this.else_branch.map(|e| e.into_owned()),
                     token: this.token.into_owned(),
                 }
             },
 
             docs = {
                 name: "For",
-                symbols = ["for <variable> in <iterable> { <block> }", "for [<variable> in] <iterable> do <block> [if <condition>]"],
+                symbols = ["for <variable> in <iterable> { <block> }", "for [<variable> in] <iterable> do <block> [if <condition>]", "for <key>, <value> in <iterable> do <block>", "for <variable> in <iterable> do <block> else <block>"],
                 description: "
                     For loops are finite value iterators. This means they map over a range, array, or object, 
                     and return a new array of values.
+                    Objects are iterated over by key, in sorted order.
                     The variable is optional, and if not provided, the loop will not bind a variable.
+                    With two variables, the first is bound to the key of an object or the index in an array or range, and the second to the value.
                     The expression will return an array of the results of the block.
                     Break and skip/continue can be used to exit the loop or skip the current iteration.
                     A condition can be provided to filter the loop.
+                    An else branch can be provided, and its value is returned instead if the block never ran.
                 ",
                 examples: "
                     for i in 0..10 { i }
@@ -156,26 +195,41 @@ define_ast!(
                     for i in {'a': 1, 'b': 2} { i }
         
                     for a in 0..10 do a if a % 2 == 0
+
+                    assert_eq(for k, v in {'a': 1, 'b': 2} do k + (v as string), ['a1', 'b2'])
+                    assert_eq(for i, v in ['x', 'y'] do i * 10 + len(v), [1, 11])
         
                     for 0..10 do '!'
+
+                    assert_eq(for i in [] do i else 'empty', 'empty')
+                    assert_eq(for i in [1, 2] do i if i > 5 else 'none', 'none')
                 ",
             }
         }
     }
 );
 
+/// Runs the loop over (key or index, value) entries
+/// A single variable is bound to the key if `binds_key` is set, and to the value otherwise
 fn iterate_over(
-    iterable: impl Iterator<Item = Value>,
+    iterable: impl Iterator<Item = (Value, Value)>,
+    binds_key: bool,
     state: &mut crate::State,
     this: &ForLoopExpression,
 ) -> Result<Value, Error> {
     let mut result = vec![];
-    for v in iterable {
+    let mut ran = false;
+    for (k, v) in iterable {
         state.check_timer().with_context(this.token())?; // Potentially long-running operation
 
         state.scope_into().with_context(this.token())?;
-        if let Some(variable) = &this.variable {
-            state.set_variable(variable, v);
+        match (&this.variable, &this.value_variable) {
+            (Some(key_variable), Some(value_variable)) => {
+                state.set_variable(key_variable, k);
+                state.set_variable(value_variable, v);
+            }
+            (Some(variable), None) => state.set_variable(variable, if binds_key { k } else { v }),
+            _ => {}
         }
         if let Some(condition) = &this.condition {
             let condition = condition.evaluate(state).with_context(this.token());
@@ -192,23 +246,25 @@ fn iterate_over(
             }
         }
 
+        ran = true;
         let value = this.body.evaluate(state);
         state.scope_out();
         match value {
             Ok(value) => result.push(value),
-            Err(e) if error_matches!(e, Skip) => {}
-            Err(e) => {
-                if let ErrorDetails::Break { value } = e.details {
-                    if let Some(value) = value {
-                        result.push(value);
-                    }
+            Err(e) => match e.details {
+                ErrorDetails::Skip { value } => result.extend(value),
+                ErrorDetails::Break { value } => {
+                    result.extend(value);
                     break;
-                } else {
-                    return Err(e);
                 }
-            }
+                _ => return Err(e),
+            },
         }
+        state.check_output_size(result.len()).with_context(this.token())?;
     }
 
-    Ok(Value::array(result))
+    match &this.else_branch {
+        Some(else_branch) if !ran => else_branch.evaluate(state),
+        _ => Ok(Value::array(result)),
+    }
 }
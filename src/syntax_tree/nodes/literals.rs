@@ -1,5 +1,9 @@
-use super::{Node, Token};
-use crate::{error::WrapExternalError, Rule};
+use super::{values::Reference, Node, Token};
+use crate::{
+    error::{ErrorDetails, WrapExternalError},
+    syntax_tree::assignment_target::AssignmentTarget,
+    Rule,
+};
 use polyvalue::{types::*, Value};
 use std::str::FromStr;
 
@@ -33,50 +37,194 @@ define_handler!(
     }
 );
 
+/// Resolves the backslash escapes in a string literal
+/// Fails on escape sequences that are not recognized, rather than passing them through
+pub(super) fn unescape_string(input: &str) -> Result<String, ErrorDetails> {
+    let mut out = String::new();
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            out.push(unescape_char(&mut chars)?);
+        } else {
+            out.push(c);
+        }
+    }
+
+    Ok(out)
+}
+
+/// Resolves a single escape sequence, from the character following the backslash
+fn unescape_char(chars: &mut std::str::Chars) -> Result<char, ErrorDetails> {
+    let invalid = |sequence: &str| ErrorDetails::InvalidEscape {
+        sequence: format!("\\{sequence}"),
+    };
+
+    match chars.next() {
+        Some('\'') => Ok('\''),
+        Some('"') => Ok('"'),
+        Some('`') => Ok('`'),
+        Some('\\') => Ok('\\'),
+        Some('n') => Ok('\n'),
+        Some('r') => Ok('\r'),
+        Some('t') => Ok('\t'),
+        Some('0') => Ok('\0'),
+
+        // \xNN - an ASCII character by its hex code
+        Some('x') => {
+            let hex = chars.by_ref().take(2).collect::<String>();
+            parse_hex(&hex, 2)
+                .filter(|c| hex.len() == 2 && *c <= 0x7F)
+                .and_then(char::from_u32)
+                .ok_or_else(|| invalid(&format!("x{hex}")))
+        }
+
+        // \u{NNNN} - a unicode character by its code point, of up to 6 hex digits
+        Some('u') => {
+            let mut sequence = "u".to_string();
+            for c in chars.by_ref() {
+                sequence.push(c);
+                if c == '}' || sequence.len() > 9 {
+                    break;
+                }
+            }
+
+            sequence
+                .strip_prefix("u{")
+                .and_then(|s| s.strip_suffix('}'))
+                .and_then(|hex| parse_hex(hex, 6))
+                .and_then(char::from_u32)
+                .ok_or_else(|| invalid(&sequence))
+        }
+
+        Some(c) => Err(invalid(&c.to_string())),
+        None => Err(invalid("")),
+    }
+}
+
+/// Parses between 1 and `max_len` hex digits
+fn parse_hex(hex: &str, max_len: usize) -> Option<u32> {
+    if hex.is_empty() || hex.len() > max_len || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        None
+    } else {
+        u32::from_str_radix(hex, 16).ok()
+    }
+}
+
+/// Removes the quotes around a string literal
+/// Multi-line strings are surrounded by 3 quotes instead of 1
+fn strip_quotes(input: &str) -> &str {
+    let n = if input.len() >= 6 && (input.starts_with("\"\"\"") || input.starts_with("'''")) {
+        3
+    } else {
+        1
+    };
+
+    // The quotes are either ' or ", so slicing by bytes is safe
+    &input[n..input.len() - n]
+}
+
 define_handler!(
     StringLiteral(_pairs, token, _state) {
-        // Remove the first and last characters - the quotes around our string
-        // This would not work great with graphemes like é, but we know that it's
-        // either ' or " so this should be safe
-        let mut c = token.input.chars();
-        c.next();
-        c.next_back();
-
-        // Now we split along our \\ backslash escapes, and rejoin after
-        // to prevent going over them twice. This method isn't super
-        // neat, there's likely a better way
-        let mut out = String::new();
-        let mut await_escape = false;
-        for char in c {
-            match char {
-                '\\' => {
-                    if await_escape {
-                        out.push('\\');
-                        await_escape = false;
-                    } else {
-                        await_escape = true;
-                    }
-                }
+        let out = unescape_string(strip_quotes(&token.input)).with_context(&token)?;
+        Ok(Node::Literal(Value::string(out), token))
+    }
+);
+document_operator!(
+    name = "Multi-line Strings",
+    rules = [],
+    symbols = ["\"\"\"...\"\"\"", "'''...'''"],
+    description = "
+        A string surrounded by 3 quotes can span multiple lines, and contain unescaped quotes.
+        Line breaks are kept as written, and backslash escapes work as they do in regular strings.
+    ",
+    examples = "
+        s = '''
+        SELECT * FROM 'users'
+        '''
+        assert_eq(s, \"\\nSELECT * FROM 'users'\\n\")
+        assert_eq(\"\"\"say \"hi\" twice\"\"\", 'say \"hi\" twice')
+    ",
+);
+
+define_handler!(
+    RawStringLiteral(_pairs, token, _state) {
+        // Remove the r prefix, the # fences, and the quotes - the contents are used as-is
+        let fence = token.input[1..].chars().take_while(|c| *c == '#').count();
+        let out = token.input[fence + 2..token.input.len() - fence - 1].to_string();
+        Ok(Node::Literal(Value::string(out), token))
+    }
+);
+document_operator!(
+    name = "Raw Strings",
+    rules = [],
+    symbols = ["r\"...\"", "r'...'", "r#\"...\"#"],
+    description = "
+        A string prefixed with `r` is taken exactly as written; backslashes are not treated as escapes.
+        This is useful for Windows paths and regular expressions.
+        To include the quote character, surround the string with any number of `#`s.
+        The string then ends at the first quote followed by the same number of `#`s.
+    ",
+    examples = "
+        assert_eq(r'C:\\temp\\new', 'C:\\\\temp\\\\new')
+        assert_eq(r#\"say \"hi\"\"#, 'say \"hi\"')
+        assert_eq(r##\"a \"# b\"##, 'a \"# b')
+    ",
+);
+
+/// Resolves the backslash escapes in a byte string literal
+/// `\\xNN` produces a single byte, and other characters are encoded as UTF-8
+pub(super) fn unescape_bytes(input: &str) -> Result<Vec<u8>, ErrorDetails> {
+    let mut out = Vec::new();
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        let c = if c != '\\' {
+            c
+        } else if chars.clone().next() == Some('x') {
+            chars.next();
+            let hex = chars.by_ref().take(2).collect::<String>();
+            match parse_hex(&hex, 2) {
+                Some(byte) if hex.len() == 2 => out.push(byte as u8),
                 _ => {
-                    if await_escape {
-                        out.push(match char {
-                            '\'' => '\'',
-                            '"' => '"',
-                            'n' => '\n',
-                            'r' => '\r',
-                            't' => '\t',
-                            _ => char,
-                        });
-                        await_escape = false;
-                    } else {
-                        out.push(char);
-                    }
+                    return Err(ErrorDetails::InvalidEscape {
+                        sequence: format!("\\x{hex}"),
+                    })
                 }
             }
-        }
+            continue;
+        } else {
+            unescape_char(&mut chars)?
+        };
 
-        Ok(Node::Literal(Value::string(out), token))
+        out.extend(c.encode_utf8(&mut [0; 4]).as_bytes());
     }
+
+    Ok(out)
+}
+
+define_handler!(
+    ByteStringLiteral(_pairs, token, _state) {
+        // Remove the b prefix and the quotes around the string
+        let input = strip_quotes(&token.input[1..]);
+        let bytes = unescape_bytes(input).with_context(&token)?;
+        let bytes = bytes.into_iter().map(|b| Value::from(U8::new(b))).collect::<Vec<_>>();
+        Ok(Node::Literal(Value::array(bytes), token))
+    }
+);
+document_operator!(
+    name = "Byte Strings",
+    rules = [],
+    symbols = ["b\"...\"", "b'...'"],
+    description = "
+        A string prefixed with `b` is an array of u8 bytes, rather than a string.
+        Characters are encoded as UTF-8, and `\\xNN` inserts a single byte with the hex value NN, which may be above 7F.
+        Other backslash escapes work as they do in regular strings.
+        Use `from_bytes` to decode a byte array as a UTF-8 string, and `to_bytes` for the reverse.
+    ",
+    examples = "
+        assert_eq(b'hi', [104u8, 105u8])
+        assert_eq(b'\\x00\\xFF', [0u8, 255u8])
+        assert_eq(from_bytes(b'caf\\xC3\\xA9'), 'café')
+    ",
 );
 
 define_handler!(
@@ -110,7 +258,12 @@ define_handler!(
 );
 
 define_handler!(
-    ConstLiteral(_pairs, token, _state) {
+    ConstLiteral(_pairs, token, state) {
+        if state.is_shadowed_constant(&token.input) {
+            let name = token.input.to_string();
+            return Ok(Reference::new(AssignmentTarget::Identifier(name), token).into());
+        }
+
         let value = match token.input.as_ref() {
             "pi" => Value::from(std::f64::consts::PI),
             "e" => Value::from(std::f64::consts::E),
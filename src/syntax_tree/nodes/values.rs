@@ -1,4 +1,4 @@
-use super::Node;
+use super::{literals::unescape_string, Node};
 use crate::{
     error::WrapExternalError,
     syntax_tree::{assignment_target::AssignmentTarget, traits::IntoNode},
@@ -215,6 +215,53 @@ define_ast!(
                     [1, 2] endswith 2
                 ",
             }
+        },
+
+        InterpolatedString(parts: Vec<Node<'i>>) {
+            build = (pairs, token, state) {
+                let parts = pairs.map(|part| {
+                    if part.as_rule() == Rule::interpolated_text {
+                        let text = unescape_string(part.as_str())
+                            .with_context(&token)?
+                            .replace("{{", "{")
+                            .replace("}}", "}");
+                        Ok(Node::Literal(Value::string(text), part.into_token()))
+                    } else {
+                        let mut part = part;
+                        unwrap_node!(part, state, token)
+                    }
+                }).collect::<Result<Vec<_>, Error>>()?;
+
+                Ok(Self { parts, token }.into())
+            },
+            eval = (this, state) {
+                let mut output = String::new();
+                for part in &this.parts {
+                    output.push_str(&part.evaluate(state).with_context(this.token())?.to_string());
+                }
+                Ok(Value::string(output))
+            },
+            owned = (this) {
+                Self::Owned {
+                    parts: this.parts.into_iter().map(|p| p.into_owned()).collect(),
+                    token: this.token.into_owned(),
+                }
+            },
+
+            docs = {
+                name: "String Interpolation",
+                symbols = ["`text {expression} text`"],
+                description: "
+                    A string enclosed in backticks can contain expressions wrapped in curly braces.
+                    Each expression is evaluated, converted to a string, and joined with the surrounding text.
+                    Use `{{` and `}}` for literal braces. Backslash escapes work as they do in regular strings.
+                ",
+                examples: "
+                    x = 5
+                    assert_eq(`x is {x}, x + 1 is {x + 1}`, 'x is 5, x + 1 is 6')
+                    assert_eq(`{{literal}}`, '{literal}')
+                ",
+            }
         }
     }
 );
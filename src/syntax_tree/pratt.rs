@@ -34,15 +34,22 @@ impl Parser {
     }
 }
 
-#[derive(Clone, Debug)]
+/// The position of an operator relative to its operands
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum PrattOperatorType {
+    /// An operator before its operand ( -a )
     Prefix,
+
+    /// An operator between two operands ( a + b )
     Infix,
+
+    /// An operator after its operand ( a++ )
     Postfix,
 }
 pub struct PrattOperator {
     ty: PrattOperatorType,
     rule: Rule,
+    symbol: &'static str,
     asoc: Assoc,
 }
 
@@ -56,29 +63,68 @@ impl PrattOperator {
     }
 }
 
+/// Structured information about an operator, for use by tooling such as syntax highlighters
+#[derive(Clone, Debug)]
+pub struct OperatorInfo {
+    /// The grammar rule for the operator
+    pub rule: Rule,
+
+    /// The operator as it appears in a script
+    pub symbol: &'static str,
+
+    /// The position of the operator relative to its operands
+    pub operator_type: PrattOperatorType,
+
+    /// True if the operator is right-associative ( a = b = c is a = (b = c) )
+    pub right_associative: bool,
+
+    /// The precedence level of the operator - higher levels bind more tightly
+    pub precedence: usize,
+}
+
+/// Lists all operators, in order of increasing precedence
+pub fn all_operators() -> Vec<OperatorInfo> {
+    PRECEDENCE_MAP
+        .iter()
+        .enumerate()
+        .flat_map(|(precedence, level)| {
+            level.iter().map(move |op| OperatorInfo {
+                rule: op.rule,
+                symbol: op.symbol,
+                operator_type: op.ty,
+                right_associative: matches!(op.asoc, Assoc::Right),
+                precedence,
+            })
+        })
+        .collect()
+}
+
 macro_rules! prefix {
-    ($rule:ident) => {
+    ($rule:ident, $symbol:literal) => {
         PrattOperator {
             ty: PrattOperatorType::Prefix,
             rule: Rule::$rule,
+            symbol: $symbol,
             asoc: Assoc::Left,
         }
     };
 }
 macro_rules! infix {
-    ($rule:ident, $asso:ident) => {
+    ($rule:ident, $symbol:literal, $asso:ident) => {
         PrattOperator {
             ty: PrattOperatorType::Infix,
             rule: Rule::$rule,
+            symbol: $symbol,
             asoc: Assoc::$asso,
         }
     };
 }
 macro_rules! postfix {
-    ($rule:ident) => {
+    ($rule:ident, $symbol:literal) => {
         PrattOperator {
             ty: PrattOperatorType::Postfix,
             rule: Rule::$rule,
+            symbol: $symbol,
             asoc: Assoc::Left,
         }
     };
@@ -90,88 +136,88 @@ macro_rules! postfix {
 pub const PRECEDENCE_MAP: &[&[PrattOperator]] = &[
     // Assignment
     &[
-        infix!(OP_ASSIGN_ADD, Right),
-        infix!(OP_ASSIGN_SUB, Right),
-        infix!(OP_ASSIGN_POW, Right),
-        infix!(OP_ASSIGN_MUL, Right),
-        infix!(OP_ASSIGN_DIV, Right),
-        infix!(OP_ASSIGN_MOD, Right),
-        infix!(OP_BASSIGN_AND, Right),
-        infix!(OP_BASSIGN_OR, Right),
-        infix!(OP_ASSIGN_OR, Right),
-        infix!(OP_ASSIGN_AND, Right),
-        infix!(OP_ASSIGN_XOR, Right),
-        infix!(OP_ASSIGN_SL, Right),
-        infix!(OP_ASSIGN_SR, Right),
-        infix!(OP_ASSIGN, Right),
+        infix!(OP_ASSIGN_ADD, "+=", Right),
+        infix!(OP_ASSIGN_SUB, "-=", Right),
+        infix!(OP_ASSIGN_POW, "**=", Right),
+        infix!(OP_ASSIGN_MUL, "*=", Right),
+        infix!(OP_ASSIGN_DIV, "/=", Right),
+        infix!(OP_ASSIGN_MOD, "%=", Right),
+        infix!(OP_BASSIGN_AND, "&&=", Right),
+        infix!(OP_BASSIGN_OR, "||=", Right),
+        infix!(OP_ASSIGN_OR, "|=", Right),
+        infix!(OP_ASSIGN_AND, "&=", Right),
+        infix!(OP_ASSIGN_XOR, "^=", Right),
+        infix!(OP_ASSIGN_SL, "<<=", Right),
+        infix!(OP_ASSIGN_SR, ">>=", Right),
+        infix!(OP_ASSIGN, "=", Right),
     ],
     // Delete
-    &[prefix!(PREFIX_DEL)],
+    &[prefix!(PREFIX_DEL, "del")],
     // Range, Ternary
-    &[infix!(OP_RANGE, Left)],
-    &[infix!(OP_TERNARY, Right)],
+    &[infix!(OP_RANGE, "..", Left)],
+    &[infix!(OP_TERNARY, "? :", Right)],
     // Decorator
-    &[postfix!(POSTFIX_DECORATE)],
+    &[postfix!(POSTFIX_DECORATE, "@")],
     //
     // Logical OR, Logical AND
-    &[infix!(OP_BOOL_OR, Left)],
-    &[infix!(OP_BOOL_AND, Left)],
+    &[infix!(OP_BOOL_OR, "||", Left)],
+    &[infix!(OP_BOOL_AND, "&&", Left)],
     //
     // Pattern Matching
     &[
-        infix!(OP_MATCH_MATCHES, Left),
-        infix!(OP_MATCH_CONTAINS, Left),
-        infix!(OP_MATCH_IS, Left),
-        infix!(OP_MATCH_STARTSWITH, Left),
-        infix!(OP_MATCH_ENDSWITH, Left),
+        infix!(OP_MATCH_MATCHES, "matches", Left),
+        infix!(OP_MATCH_CONTAINS, "contains", Left),
+        infix!(OP_MATCH_IS, "is", Left),
+        infix!(OP_MATCH_STARTSWITH, "starts_with", Left),
+        infix!(OP_MATCH_ENDSWITH, "ends_with", Left),
     ],
     //
     // Bitwise OR, Bitwise XOR, Bitwise AND
-    &[infix!(OP_BIT_OR, Left)],
-    &[infix!(OP_BIT_XOR, Left)],
-    &[infix!(OP_BIT_AND, Left)],
+    &[infix!(OP_BIT_OR, "|", Left)],
+    &[infix!(OP_BIT_XOR, "^", Left)],
+    &[infix!(OP_BIT_AND, "&", Left)],
     //
     // == and !=, followed by <, <=, >, >=
     &[
-        infix!(OP_BOOL_EQ, Left),
-        infix!(OP_BOOL_NE, Left),
-        infix!(OP_BOOL_SEQ, Left),
-        infix!(OP_BOOL_SNE, Left),
+        infix!(OP_BOOL_EQ, "==", Left),
+        infix!(OP_BOOL_NE, "!=", Left),
+        infix!(OP_BOOL_SEQ, "===", Left),
+        infix!(OP_BOOL_SNE, "!==", Left),
     ],
     &[
-        infix!(OP_BOOL_LT, Left),
-        infix!(OP_BOOL_LE, Left),
-        infix!(OP_BOOL_GT, Left),
-        infix!(OP_BOOL_GE, Left),
+        infix!(OP_BOOL_LT, "<", Left),
+        infix!(OP_BOOL_LE, "<=", Left),
+        infix!(OP_BOOL_GT, ">", Left),
+        infix!(OP_BOOL_GE, ">=", Left),
     ],
     //
     // << and >>
-    &[infix!(OP_BIT_SL, Left), infix!(OP_BIT_SR, Left)],
+    &[infix!(OP_BIT_SL, "<<", Left), infix!(OP_BIT_SR, ">>", Left)],
     //
     // Add and subtract, followed by multiply divide and mod, then pow
-    &[infix!(OP_ADD, Left), infix!(OP_SUB, Left)],
+    &[infix!(OP_ADD, "+", Left), infix!(OP_SUB, "-", Left)],
     &[
-        infix!(OP_MUL, Left),
-        infix!(OP_DIV, Left),
-        infix!(OP_MOD, Left),
+        infix!(OP_MUL, "*", Left),
+        infix!(OP_DIV, "/", Left),
+        infix!(OP_MOD, "%", Left),
     ],
-    &[infix!(OP_POW, Right)],
+    &[infix!(OP_POW, "**", Right)],
     //
     // Cast, and prefix operators
     &[
-        infix!(OP_CAST, Right),
-        prefix!(PREFIX_NEG),
-        prefix!(PREFIX_INC),
-        prefix!(PREFIX_DEC),
-        prefix!(PREFIX_BOOL_NOT),
-        prefix!(PREFIX_BIT_NOT),
+        infix!(OP_CAST, "as", Right),
+        prefix!(PREFIX_NEG, "-"),
+        prefix!(PREFIX_INC, "++"),
+        prefix!(PREFIX_DEC, "--"),
+        prefix!(PREFIX_BOOL_NOT, "!"),
+        prefix!(PREFIX_BIT_NOT, "~"),
     ],
     //
     // Postfix operators
     &[
-        postfix!(POSTFIX_INC),
-        postfix!(POSTFIX_DEC),
-        postfix!(POSTFIX_CALL),
-        postfix!(POSTFIX_INDEX),
+        postfix!(POSTFIX_INC, "++"),
+        postfix!(POSTFIX_DEC, "--"),
+        postfix!(POSTFIX_CALL, "()"),
+        postfix!(POSTFIX_INDEX, "[]"),
     ],
 ];